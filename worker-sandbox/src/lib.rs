@@ -61,6 +61,14 @@ async fn handle_async_request<D>(req: Request, _ctx: RouteContext<D>) -> Result<
     ))
 }
 
+fn require_admin<D>(req: &Request, _ctx: &RouteContext<D>) -> Result<Option<Response>> {
+    if req.headers().get("x-admin")?.is_some() {
+        return Ok(None);
+    }
+
+    Response::error("Forbidden", 403).map(Some)
+}
+
 #[event(fetch)]
 pub async fn main(req: Request, env: Env) -> Result<Response> {
     utils::set_panic_hook();
@@ -82,6 +90,11 @@ pub async fn main(req: Request, env: Env) -> Result<Response> {
     router
         .get("/request", handle_a_request) // can pass a fn pointer to keep routes tidy
         .get_async("/async-request", handle_async_request)
+        .group("/admin", |admin| {
+            admin
+                .middleware(require_admin)
+                .get("/status", |_, _| Response::ok("admin ok"))
+        })
         .get("/test-data", |_, ctx| {
             // just here to test data works
             if ctx.data().regex.is_match("2014-01-01") {
@@ -238,6 +251,18 @@ pub async fn main(req: Request, env: Env) -> Result<Response> {
                 resp2.status_code()
             ))
         })
+        .get_async("/fetch-abort", |_req, _ctx| async move {
+            let controller = AbortController::new();
+            let mut init = RequestInit::new();
+            init.with_signal(Some(&controller.signal()));
+
+            let req = Request::new_with_init("https://example.com", &init)?;
+            controller.abort();
+            match Fetch::Request(req).send().await {
+                Ok(_) => Response::error("request was not aborted", 500),
+                Err(e) => Response::ok(format!("request was aborted: {}", e)),
+            }
+        })
         .get_async("/fetch_json", |_req, _ctx| async move {
             let data: ApiData = Fetch::Url(
                 "https://jsonplaceholder.typicode.com/todos/1"
@@ -274,6 +299,11 @@ pub async fn main(req: Request, env: Env) -> Result<Response> {
 
             Fetch::Url(url.parse()?).send().await
         })
+        .post_async("/proxy_upload/*url", |req, ctx| async move {
+            let url = ctx.param("url").unwrap().strip_prefix('/').unwrap();
+
+            Fetch::Request(req.forward_to(url)?).send().await
+        })
         .get_async("/durable/:id", |_req, ctx| async move {
             let namespace = ctx.durable_object("COUNTER")?;
             let stub = namespace.id_from_name("A")?.get_stub()?;
@@ -298,6 +328,26 @@ pub async fn main(req: Request, env: Env) -> Result<Response> {
         .get("/bytes", |_, _| {
             Response::from_bytes(vec![1, 2, 3, 4, 5, 6, 7])
         })
+        .get("/json-array-stream", |_, _| {
+            let items = futures::stream::iter((1..=3).map(|id| {
+                Ok::<_, Error>(MyData {
+                    message: format!("item {}", id),
+                    is: id % 2 == 0,
+                    data: vec![id],
+                })
+            }));
+            Response::json_array_stream(items)
+        })
+        .get("/stream-with-length", |_, _| {
+            let chunks = futures::stream::iter(vec![
+                Ok::<_, Error>(b"hello, ".to_vec()),
+                Ok(b"world".to_vec()),
+            ]);
+            Response::from_stream_with_length(chunks, 12)
+        })
+        .post("/redirect-after-post", |_, _| {
+            Response::see_other("/request")
+        })
         .post_async("/api-data", |mut req, _ctx| async move {
             let data = req.bytes().await?;
             let mut todo: ApiData = serde_json::from_slice(&data)?;
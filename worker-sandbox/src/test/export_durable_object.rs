@@ -97,7 +97,16 @@ impl DurableObject for MyClass {
                     Response::ok(self.number.to_string())
                 }
                 "/transaction" => {
-                    Response::error("transactional storage API is still unstable", 501)
+                    let mut storage = self.state.storage();
+                    storage
+                        .transaction(|mut txn| async move {
+                            let count: usize = txn.get("count").await.unwrap_or(0);
+                            txn.put("count", count + 1).await
+                        })
+                        .await?;
+
+                    self.number = storage.get("count").await?;
+                    Response::ok(self.number.to_string())
                 }
                 _ => Response::error("Not Found", 404),
             }
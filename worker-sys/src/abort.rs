@@ -0,0 +1,41 @@
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=::js_sys::Object, js_name=AbortController)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub type AbortController;
+
+    #[wasm_bindgen(catch, constructor, js_class=AbortController)]
+    pub fn new() -> Result<AbortController, JsValue>;
+
+    #[wasm_bindgen(structural, method, getter, js_class=AbortController, js_name=signal)]
+    pub fn signal(this: &AbortController) -> AbortSignal;
+
+    #[wasm_bindgen(structural, method, js_class=AbortController, js_name=abort)]
+    pub fn abort(this: &AbortController);
+
+    #[wasm_bindgen(structural, method, js_class=AbortController, js_name=abort)]
+    pub fn abort_with_reason(this: &AbortController, reason: &JsValue);
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=crate::global::EventTarget, extends=::js_sys::Object, js_name=AbortSignal)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub type AbortSignal;
+
+    #[wasm_bindgen(structural, method, getter, js_class=AbortSignal, js_name=aborted)]
+    pub fn aborted(this: &AbortSignal) -> bool;
+
+    #[wasm_bindgen(structural, method, getter, js_class=AbortSignal, js_name=reason)]
+    pub fn reason(this: &AbortSignal) -> JsValue;
+
+    #[wasm_bindgen(structural, method, js_class=AbortSignal, js_name=addEventListener)]
+    pub fn add_event_listener_with_callback_and_options(
+        this: &AbortSignal,
+        event_type: &str,
+        listener: &::js_sys::Function,
+        options: &JsValue,
+    );
+}
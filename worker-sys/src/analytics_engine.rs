@@ -0,0 +1,14 @@
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=::js_sys::Object, js_name=AnalyticsEngineDataset)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub type AnalyticsEngineDataset;
+
+    #[wasm_bindgen(structural, catch, method, js_class=AnalyticsEngineDataset, js_name=writeDataPoint)]
+    pub fn write_data_point(
+        this: &AnalyticsEngineDataset,
+        event: &::js_sys::Object,
+    ) -> Result<(), JsValue>;
+}
@@ -56,6 +56,31 @@ extern "C" {
 
     #[wasm_bindgen(structural, method, getter, js_class=IncomingRequestCfProperties, js_name=timezone)]
     pub fn timezone(this: &Cf) -> String;
+
+    #[wasm_bindgen(structural, method, getter, js_class=IncomingRequestCfProperties, js_name=clientTcpRtt)]
+    pub fn client_tcp_rtt(this: &Cf) -> Option<u32>;
+
+    #[wasm_bindgen(structural, method, getter, js_class=IncomingRequestCfProperties, js_name=clientAcceptEncoding)]
+    pub fn client_accept_encoding(this: &Cf) -> Option<String>;
+
+    #[wasm_bindgen(structural, method, getter, js_class=IncomingRequestCfProperties, js_name=botManagement)]
+    pub fn bot_management(this: &Cf) -> Option<BotManagement>;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=::js_sys::Object, js_name=botManagement)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub type BotManagement;
+
+    #[wasm_bindgen(structural, method, getter, js_name=score, js_class = "botManagement")]
+    pub fn score(this: &BotManagement) -> Option<u32>;
+
+    #[wasm_bindgen(structural, method, getter, js_name=verifiedBot, js_class = "botManagement")]
+    pub fn verified_bot(this: &BotManagement) -> Option<bool>;
+
+    #[wasm_bindgen(structural, method, getter, js_name=ja3Hash, js_class = "botManagement")]
+    pub fn ja3_hash(this: &BotManagement) -> Option<String>;
 }
 
 #[wasm_bindgen]
@@ -0,0 +1,36 @@
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=::js_sys::Object, js_name=Crypto)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub type Crypto;
+
+    #[wasm_bindgen(structural, method, getter, js_class=Crypto, js_name=subtle)]
+    pub fn subtle(this: &Crypto) -> SubtleCrypto;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=::js_sys::Object, js_name=SubtleCrypto)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub type SubtleCrypto;
+
+    #[wasm_bindgen(catch, structural, method, js_class=SubtleCrypto, js_name=importKey)]
+    pub fn import_key_with_u8_array(
+        this: &SubtleCrypto,
+        format: &str,
+        key_data: &[u8],
+        algorithm: &::js_sys::Object,
+        extractable: bool,
+        key_usages: &::js_sys::Array,
+    ) -> Result<::js_sys::Promise, JsValue>;
+
+    #[wasm_bindgen(catch, structural, method, js_class=SubtleCrypto, js_name=sign)]
+    pub fn sign_with_u8_array(
+        this: &SubtleCrypto,
+        algorithm: &str,
+        key: &::js_sys::Object,
+        data: &[u8],
+    ) -> Result<::js_sys::Promise, JsValue>;
+}
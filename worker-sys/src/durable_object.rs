@@ -28,6 +28,12 @@ extern "C" {
     #[wasm_bindgen(method, getter, js_class = "DurableObjectState", js_name = storage)]
     pub fn storage_internal(this: &ObjectState) -> ObjectStorage;
 
+    #[wasm_bindgen(catch, method, js_class = "DurableObjectState", js_name = blockConcurrencyWhile)]
+    pub fn block_concurrency_while_internal(
+        this: &ObjectState,
+        callback: &Closure<dyn FnMut() -> ::js_sys::Promise>,
+    ) -> StdResult<::js_sys::Promise, JsValue>;
+
     #[wasm_bindgen (catch, method, js_class = "DurableObjectNamespace", js_name = idFromName)]
     pub fn id_from_name_internal(
         this: &ObjectNamespace,
@@ -113,7 +119,7 @@ extern "C" {
     #[wasm_bindgen(catch, method, js_class = "DurableObjectStorage", js_name = transaction)]
     pub fn transaction_internal(
         this: &ObjectStorage,
-        closure: &Closure<dyn FnMut(ObjectTransaction)>,
+        closure: &Closure<dyn FnMut(ObjectTransaction) -> ::js_sys::Promise>,
     ) -> StdResult<::js_sys::Promise, JsValue>;
 }
 
@@ -0,0 +1,16 @@
+use wasm_bindgen::prelude::*;
+
+use crate::request::Request;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=::js_sys::Object, js_name=Fetcher)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub type Fetcher;
+
+    #[wasm_bindgen(structural, method, js_class=Fetcher, js_name=fetch)]
+    pub fn fetch_with_request(this: &Fetcher, req: &Request) -> ::js_sys::Promise;
+
+    #[wasm_bindgen(structural, method, js_class=Fetcher, js_name=fetch)]
+    pub fn fetch_with_str(this: &Fetcher, url: &str) -> ::js_sys::Promise;
+}
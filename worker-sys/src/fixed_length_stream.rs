@@ -0,0 +1,17 @@
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=::js_sys::Object, js_name=FixedLengthStream)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub type FixedLengthStream;
+
+    #[wasm_bindgen(catch, constructor, js_class=FixedLengthStream)]
+    pub fn new(length: f64) -> Result<FixedLengthStream, JsValue>;
+
+    #[wasm_bindgen(structural, method, getter, js_class=FixedLengthStream, js_name=readable)]
+    pub fn readable(this: &FixedLengthStream) -> web_sys::ReadableStream;
+
+    #[wasm_bindgen(structural, method, getter, js_class=FixedLengthStream, js_name=writable)]
+    pub fn writable(this: &FixedLengthStream) -> ::js_sys::Object;
+}
@@ -15,6 +15,15 @@ extern "C" {
     #[doc = "The `append()` method."]
     pub fn append_with_str(this: &FormData, name: &str, value: &str) -> Result<(), JsValue>;
 
+    # [wasm_bindgen (catch , method , structural , js_class = "FormData" , js_name = append)]
+    #[doc = "The `append()` method."]
+    pub fn append_with_blob_and_filename(
+        this: &FormData,
+        name: &str,
+        value: &web_sys::Blob,
+        filename: &str,
+    ) -> Result<(), JsValue>;
+
     # [wasm_bindgen (method , structural , js_class = "FormData" , js_name = delete)]
     #[doc = "The `delete()` method."]
     pub fn delete(this: &FormData, name: &str);
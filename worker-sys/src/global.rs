@@ -23,6 +23,10 @@ extern "C" {
     #[doc = "The `btoa()` method."]
     pub fn btoa(this: &WorkerGlobalScope, btoa: &str) -> Result<String, JsValue>;
 
+    #[wasm_bindgen (method , structural , getter , js_class = "WorkerGlobalScope" , js_name = crypto)]
+    #[doc = "Getter for the `crypto` field of this object."]
+    pub fn crypto(this: &WorkerGlobalScope) -> crate::Crypto;
+
     #[wasm_bindgen (method , structural , js_class = "WorkerGlobalScope" , js_name = fetch)]
     #[doc = "The `fetch()` method."]
     pub fn fetch_with_request(this: &WorkerGlobalScope, input: &Request) -> ::js_sys::Promise;
@@ -47,6 +51,29 @@ extern "C" {
         init: &RequestInit,
     ) -> ::js_sys::Promise;
 
+    #[wasm_bindgen (catch , method , structural , js_class = "WorkerGlobalScope" , js_name = setTimeout)]
+    #[doc = "The `setTimeout()` method."]
+    pub fn set_timeout_with_callback_and_timeout(
+        this: &WorkerGlobalScope,
+        handler: &::js_sys::Function,
+        timeout: i32,
+    ) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen (method , structural , js_class = "WorkerGlobalScope" , js_name = clearTimeout)]
+    #[doc = "The `clearTimeout()` method."]
+    pub fn clear_timeout_with_handle(this: &WorkerGlobalScope, handle: &JsValue);
+
     #[wasm_bindgen(js_namespace = console)]
     pub fn log(s: &str);
+
+    #[wasm_bindgen(js_namespace = console)]
+    pub fn error(s: &str);
+
+    #[wasm_bindgen(js_namespace = crypto, js_name = randomUUID)]
+    #[doc = "The `crypto.randomUUID()` method."]
+    pub fn random_uuid() -> String;
+
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    #[doc = "The `performance.now()` method."]
+    pub fn performance_now() -> f64;
 }
@@ -0,0 +1,26 @@
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=::js_sys::Object, js_name=Hyperdrive)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub type Hyperdrive;
+
+    #[wasm_bindgen(structural, method, getter, js_class=Hyperdrive, js_name=connectionString)]
+    pub fn connection_string(this: &Hyperdrive) -> String;
+
+    #[wasm_bindgen(structural, method, getter, js_class=Hyperdrive, js_name=host)]
+    pub fn host(this: &Hyperdrive) -> String;
+
+    #[wasm_bindgen(structural, method, getter, js_class=Hyperdrive, js_name=port)]
+    pub fn port(this: &Hyperdrive) -> u16;
+
+    #[wasm_bindgen(structural, method, getter, js_class=Hyperdrive, js_name=user)]
+    pub fn user(this: &Hyperdrive) -> String;
+
+    #[wasm_bindgen(structural, method, getter, js_class=Hyperdrive, js_name=password)]
+    pub fn password(this: &Hyperdrive) -> String;
+
+    #[wasm_bindgen(structural, method, getter, js_class=Hyperdrive, js_name=database)]
+    pub fn database(this: &Hyperdrive) -> String;
+}
@@ -1,12 +1,19 @@
+pub mod abort;
+pub mod analytics_engine;
 pub mod cf;
+pub mod crypto;
 pub mod durable_object;
+pub mod fetcher;
 pub mod file;
+pub mod fixed_length_stream;
 pub mod formdata;
 pub mod global;
 pub mod headers;
+pub mod hyperdrive;
 pub mod request;
 pub mod request_init;
 pub mod response;
+pub mod transform_stream;
 
 /// When debugging your Worker via `wrangler dev`, `wrangler tail`, or from the Workers Dashboard,
 /// anything passed to this macro will be printed to the terminal or written to the console.
@@ -15,26 +22,48 @@ macro_rules! console_log {
     ($($t:tt)*) => (unsafe { $crate::global::log(&format_args!($($t)*).to_string()) })
 }
 
+/// Like `console_log!`, but written to the console as an error, so it is highlighted as such by
+/// `wrangler tail` and the Workers Dashboard.
+#[macro_export]
+macro_rules! console_error {
+    ($($t:tt)*) => (unsafe { $crate::global::error(&format_args!($($t)*).to_string()) })
+}
+
 pub mod prelude {
+    pub use crate::abort::{AbortController, AbortSignal};
+    pub use crate::analytics_engine::AnalyticsEngineDataset;
     pub use crate::cf::Cf;
+    pub use crate::console_error;
     pub use crate::console_log;
+    pub use crate::crypto::{Crypto, SubtleCrypto};
     pub use crate::durable_object;
+    pub use crate::fetcher::Fetcher;
     pub use crate::file::File;
+    pub use crate::fixed_length_stream::FixedLengthStream;
     pub use crate::formdata::FormData;
     pub use crate::global::WorkerGlobalScope;
     pub use crate::headers::Headers;
+    pub use crate::hyperdrive::Hyperdrive;
     pub use crate::request::Request;
     pub use crate::request_init::*;
     pub use crate::response::Response;
+    pub use crate::transform_stream::{CompressionStream, TransformStream};
 }
 
+pub use abort::{AbortController, AbortSignal};
+pub use analytics_engine::AnalyticsEngineDataset;
 pub use cf::Cf;
+pub use crypto::{Crypto, SubtleCrypto};
 pub use durable_object::*;
+pub use fetcher::Fetcher;
 pub use file::File;
+pub use fixed_length_stream::FixedLengthStream;
 pub use formdata::FormData;
 pub use global::WorkerGlobalScope;
 pub use headers::Headers;
+pub use hyperdrive::Hyperdrive;
 pub use request::Request;
 pub use request_init::*;
 pub use response::Response;
+pub use transform_stream::{CompressionStream, TransformStream};
 pub use web_sys::ResponseInit;
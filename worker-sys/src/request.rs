@@ -19,7 +19,13 @@ extern "C" {
     pub fn headers(this: &Request) -> crate::headers::Headers;
 
     #[wasm_bindgen(structural, method, getter, js_class=Request, js_name=redirect)]
-    pub fn redirect(this: &Request) -> web_sys::RequestRedirect;
+    pub fn redirect(this: &Request) -> crate::RequestRedirect;
+
+    #[wasm_bindgen(structural, method, getter, js_class=Request, js_name=mode)]
+    pub fn mode(this: &Request) -> Option<String>;
+
+    #[wasm_bindgen(structural, method, getter, js_class=Request, js_name=credentials)]
+    pub fn credentials(this: &Request) -> Option<String>;
 
     #[wasm_bindgen(structural, method, getter, js_class=Request, js_name=bodyUsed)]
     pub fn body_used(this: &Request) -> bool;
@@ -27,6 +33,9 @@ extern "C" {
     #[wasm_bindgen(structural, method, getter, js_class=Request, js_name=body)]
     pub fn body(this: &Request) -> Option<web_sys::ReadableStream>;
 
+    #[wasm_bindgen(structural, method, getter, js_class=Request, js_name=trailers)]
+    pub fn trailers(this: &Request) -> JsValue;
+
     #[wasm_bindgen(catch, constructor, js_class=Request)]
     pub fn new_with_request(input: &Request) -> Result<Request, JsValue>;
 
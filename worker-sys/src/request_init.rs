@@ -77,6 +77,56 @@ impl RequestInit {
         let _ = r;
         self
     }
+
+    #[doc = "Change the `mode` field of this object."]
+    pub fn mode(&mut self, val: &str) -> &mut Self {
+        #[allow(unused_unsafe)]
+        let r = unsafe {
+            ::js_sys::Reflect::set(self.as_ref(), &JsValue::from("mode"), &JsValue::from(val))
+        };
+        debug_assert!(
+            r.is_ok(),
+            "setting properties should never fail on our dictionary objects"
+        );
+        let _ = r;
+        self
+    }
+
+    #[doc = "Change the `credentials` field of this object."]
+    pub fn credentials(&mut self, val: &str) -> &mut Self {
+        #[allow(unused_unsafe)]
+        let r = unsafe {
+            ::js_sys::Reflect::set(
+                self.as_ref(),
+                &JsValue::from("credentials"),
+                &JsValue::from(val),
+            )
+        };
+        debug_assert!(
+            r.is_ok(),
+            "setting properties should never fail on our dictionary objects"
+        );
+        let _ = r;
+        self
+    }
+
+    #[doc = "Change the `signal` field of this object."]
+    pub fn signal(&mut self, val: Option<&crate::AbortSignal>) -> &mut Self {
+        #[allow(unused_unsafe)]
+        let r = unsafe {
+            ::js_sys::Reflect::set(
+                self.as_ref(),
+                &JsValue::from("signal"),
+                &JsValue::from(val.cloned()),
+            )
+        };
+        debug_assert!(
+            r.is_ok(),
+            "setting properties should never fail on our dictionary objects"
+        );
+        let _ = r;
+        self
+    }
 }
 
 impl Default for RequestInit {
@@ -0,0 +1,39 @@
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=::js_sys::Object, js_name=TransformStream)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub type TransformStream;
+
+    #[wasm_bindgen(catch, constructor, js_class=TransformStream)]
+    pub fn new() -> Result<TransformStream, JsValue>;
+
+    #[wasm_bindgen(catch, constructor, js_class=TransformStream)]
+    pub fn new_with_transformer(transformer: &::js_sys::Object)
+        -> Result<TransformStream, JsValue>;
+
+    #[wasm_bindgen(structural, method, getter, js_class=TransformStream, js_name=readable)]
+    pub fn readable(this: &TransformStream) -> web_sys::ReadableStream;
+
+    #[wasm_bindgen(structural, method, getter, js_class=TransformStream, js_name=writable)]
+    pub fn writable(this: &TransformStream) -> ::js_sys::Object;
+
+    #[wasm_bindgen(extends=::js_sys::Object, js_name=TransformStreamDefaultController)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub type TransformStreamDefaultController;
+
+    #[wasm_bindgen(structural, catch, method, js_class=TransformStreamDefaultController, js_name=enqueue)]
+    pub fn enqueue(this: &TransformStreamDefaultController, chunk: &JsValue)
+        -> Result<(), JsValue>;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends=::js_sys::Object, js_name=CompressionStream)]
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub type CompressionStream;
+
+    #[wasm_bindgen(catch, constructor, js_class=CompressionStream)]
+    pub fn new(format: &str) -> Result<CompressionStream, JsValue>;
+}
@@ -0,0 +1,138 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use js_sys::{Object, Promise};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use worker_sys::{AbortController as EdgeAbortController, AbortSignal as EdgeAbortSignal};
+
+/// An [AbortController](https://developer.mozilla.org/en-US/docs/Web/API/AbortController)
+/// representation, used to cancel outbound requests and any other work observing its
+/// `AbortSignal`.
+/// ```ignore
+/// let controller = AbortController::new();
+/// let mut init = RequestInit::new();
+/// init.with_signal(Some(&controller.signal()));
+///
+/// let req = Request::new_with_init("https://example.com", &init)?;
+/// controller.abort();
+/// assert!(Fetch::Request(req).send().await.is_err());
+/// ```
+#[derive(Debug)]
+pub struct AbortController {
+    inner: EdgeAbortController,
+}
+
+impl AbortController {
+    /// Construct a new `AbortController`.
+    pub fn new() -> Self {
+        // This cannot throw an error: https://developer.mozilla.org/en-US/docs/Web/API/AbortController/AbortController
+        Self {
+            inner: EdgeAbortController::new().unwrap(),
+        }
+    }
+
+    /// Get an `AbortSignal` instance which can be used to observe and respond to this controller
+    /// being aborted.
+    pub fn signal(&self) -> AbortSignal {
+        AbortSignal {
+            inner: self.inner.signal(),
+        }
+    }
+
+    /// Abort any operation which is observing this controller's signal.
+    pub fn abort(&self) {
+        self.inner.abort()
+    }
+
+    /// Abort any operation which is observing this controller's signal, providing the reason for
+    /// aborting.
+    pub fn abort_with_reason(&self, reason: impl Into<JsValue>) {
+        self.inner.abort_with_reason(&reason.into())
+    }
+}
+
+impl Default for AbortController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An [AbortSignal](https://developer.mozilla.org/en-US/docs/Web/API/AbortSignal) representation,
+/// used to observe the cancellation of its associated `AbortController`.
+#[derive(Debug, Clone)]
+pub struct AbortSignal {
+    inner: EdgeAbortSignal,
+}
+
+impl AbortSignal {
+    /// Whether the controller associated with this signal has been aborted.
+    pub fn aborted(&self) -> bool {
+        self.inner.aborted()
+    }
+
+    /// The reason provided when the associated controller was aborted, if it has been aborted.
+    pub fn reason(&self) -> Option<JsValue> {
+        if self.aborted() {
+            Some(self.inner.reason())
+        } else {
+            None
+        }
+    }
+
+    /// Get a `Future` which resolves once the associated controller has been aborted, so that
+    /// long-running work can be raced against cancellation, e.g. using `futures::future::select`.
+    /// Resolves immediately if the signal has already been aborted.
+    pub fn wait_for_abort(&self) -> AbortFuture {
+        let signal = self.inner.clone();
+        let promise = Promise::new(&mut |resolve, _reject| {
+            if signal.aborted() {
+                let _ = resolve.call0(&JsValue::UNDEFINED);
+                return;
+            }
+
+            let options = Object::new();
+            #[allow(unused_unsafe)]
+            let r =
+                unsafe { js_sys::Reflect::set(&options, &JsValue::from("once"), &JsValue::TRUE) };
+            debug_assert!(
+                r.is_ok(),
+                "setting properties should never fail on our dictionary objects"
+            );
+            let _ = r;
+
+            signal.add_event_listener_with_callback_and_options("abort", &resolve, &options);
+        });
+
+        AbortFuture {
+            inner: JsFuture::from(promise),
+        }
+    }
+
+    pub fn inner(&self) -> &EdgeAbortSignal {
+        &self.inner
+    }
+}
+
+impl From<EdgeAbortSignal> for AbortSignal {
+    fn from(inner: EdgeAbortSignal) -> Self {
+        Self { inner }
+    }
+}
+
+/// A `Future` which resolves once an `AbortSignal` has been aborted. Created by calling
+/// `AbortSignal::wait_for_abort`.
+pub struct AbortFuture {
+    inner: JsFuture,
+}
+
+impl Future for AbortFuture {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.inner).poll(cx).map(|_| ())
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{request_init::set_prop, Error, Result};
+
+use js_sys::{Array, Object};
+use wasm_bindgen::JsValue;
+use worker_sys::AnalyticsEngineDataset;
+
+const MAX_INDEXES: usize = 1;
+const MAX_INDEX_BYTES: usize = 96;
+const MAX_BLOBS: usize = 20;
+const MAX_BLOB_BYTES: usize = 16 * 1024;
+const MAX_DOUBLES: usize = 20;
+
+/// A binding to a Workers [Analytics Engine](https://developers.cloudflare.com/analytics/analytics-engine/)
+/// dataset, used to record custom metrics.
+/// ```ignore
+/// let dataset = ctx.analytics_engine("REQUESTS")?;
+/// dataset.write_data_point(&AnalyticsDataPoint {
+///     indexes: vec![customer_id],
+///     blobs: vec![req.path(), country],
+///     doubles: vec![elapsed_ms],
+/// })?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct AnalyticsEngine {
+    inner: AnalyticsEngineDataset,
+}
+
+impl AnalyticsEngine {
+    /// Write a data point to the dataset. Writes are not awaited: the runtime sends them in the
+    /// background once the Worker has responded. Returns an error without writing anything if the
+    /// data point exceeds the limits described on `AnalyticsDataPoint`.
+    pub fn write_data_point(&self, data_point: &AnalyticsDataPoint) -> Result<()> {
+        data_point.validate()?;
+
+        let event = Object::new();
+        set_prop(
+            &event,
+            &JsValue::from("indexes"),
+            &to_array(&data_point.indexes),
+        );
+        set_prop(
+            &event,
+            &JsValue::from("blobs"),
+            &to_array(&data_point.blobs),
+        );
+        set_prop(
+            &event,
+            &JsValue::from("doubles"),
+            &data_point
+                .doubles
+                .iter()
+                .map(|double| JsValue::from_f64(*double))
+                .collect::<Array>(),
+        );
+
+        self.inner.write_data_point(&event).map_err(Error::from)
+    }
+}
+
+impl From<AnalyticsEngineDataset> for AnalyticsEngine {
+    fn from(inner: AnalyticsEngineDataset) -> Self {
+        Self { inner }
+    }
+}
+
+/// A data point written to an Analytics Engine dataset. Datasets accept at most one index of up
+/// to 96 bytes, which is used to sample data points, up to 20 blobs totaling no more than 16 KB,
+/// and up to 20 doubles.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnalyticsDataPoint {
+    pub indexes: Vec<String>,
+    pub blobs: Vec<String>,
+    pub doubles: Vec<f64>,
+}
+
+impl AnalyticsDataPoint {
+    /// Check the data point against the documented limits of Analytics Engine.
+    pub fn validate(&self) -> Result<()> {
+        if self.indexes.len() > MAX_INDEXES {
+            return Err(Error::RustError(format!(
+                "data points can have at most {} index, found {}",
+                MAX_INDEXES,
+                self.indexes.len()
+            )));
+        }
+        if let Some(index) = self.indexes.iter().find(|i| i.len() > MAX_INDEX_BYTES) {
+            return Err(Error::RustError(format!(
+                "data point indexes can be at most {} bytes, found {}",
+                MAX_INDEX_BYTES,
+                index.len()
+            )));
+        }
+        if self.blobs.len() > MAX_BLOBS {
+            return Err(Error::RustError(format!(
+                "data points can have at most {} blobs, found {}",
+                MAX_BLOBS,
+                self.blobs.len()
+            )));
+        }
+        let blob_bytes: usize = self.blobs.iter().map(String::len).sum();
+        if blob_bytes > MAX_BLOB_BYTES {
+            return Err(Error::RustError(format!(
+                "data point blobs can total at most {} bytes, found {}",
+                MAX_BLOB_BYTES, blob_bytes
+            )));
+        }
+        if self.doubles.len() > MAX_DOUBLES {
+            return Err(Error::RustError(format!(
+                "data points can have at most {} doubles, found {}",
+                MAX_DOUBLES,
+                self.doubles.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+fn to_array(values: &[String]) -> Array {
+    values.iter().map(JsValue::from).collect()
+}
+
+#[test]
+fn data_points_are_validated_against_limits() {
+    let data_point = AnalyticsDataPoint {
+        indexes: vec!["customer".into()],
+        blobs: vec!["/".into(); MAX_BLOBS],
+        doubles: vec![1.0; MAX_DOUBLES],
+    };
+    assert!(data_point.validate().is_ok());
+
+    let too_many_indexes = AnalyticsDataPoint {
+        indexes: vec!["a".into(), "b".into()],
+        ..AnalyticsDataPoint::default()
+    };
+    assert!(too_many_indexes.validate().is_err());
+
+    let long_index = AnalyticsDataPoint {
+        indexes: vec!["a".repeat(MAX_INDEX_BYTES + 1)],
+        ..AnalyticsDataPoint::default()
+    };
+    assert!(long_index.validate().is_err());
+
+    let large_blobs = AnalyticsDataPoint {
+        blobs: vec![
+            "a".repeat(MAX_BLOB_BYTES / 2),
+            "b".repeat(MAX_BLOB_BYTES / 2 + 1),
+        ],
+        ..AnalyticsDataPoint::default()
+    };
+    assert!(large_blobs.validate().is_err());
+
+    let too_many_doubles = AnalyticsDataPoint {
+        doubles: vec![1.0; MAX_DOUBLES + 1],
+        ..AnalyticsDataPoint::default()
+    };
+    assert!(too_many_doubles.validate().is_err());
+}
@@ -0,0 +1,136 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visibility {
+    Public,
+    Private,
+}
+
+/// A builder for the value of a [Cache-Control](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Cache-Control)
+/// response header. Directives are always written in the same order, regardless of the order in
+/// which they were set.
+/// ```ignore
+/// let cc = CacheControl::new()
+///     .public()
+///     .max_age(Duration::from_secs(60))
+///     .stale_while_revalidate(Duration::from_secs(30));
+///
+/// Response::ok("cached")?.with_cache_control(cc)
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    visibility: Option<Visibility>,
+    no_cache: bool,
+    no_store: bool,
+    max_age: Option<Duration>,
+    s_maxage: Option<Duration>,
+    stale_while_revalidate: Option<Duration>,
+    immutable: bool,
+}
+
+impl CacheControl {
+    /// Construct an empty `CacheControl`, with no directives set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the `public` directive, replacing `private` if it was set.
+    pub fn public(mut self) -> Self {
+        self.visibility = Some(Visibility::Public);
+        self
+    }
+
+    /// Set the `private` directive, replacing `public` if it was set.
+    pub fn private(mut self) -> Self {
+        self.visibility = Some(Visibility::Private);
+        self
+    }
+
+    /// Set the `no-cache` directive.
+    pub fn no_cache(mut self) -> Self {
+        self.no_cache = true;
+        self
+    }
+
+    /// Set the `no-store` directive.
+    pub fn no_store(mut self) -> Self {
+        self.no_store = true;
+        self
+    }
+
+    /// Set the `max-age` directive, truncated to whole seconds.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Set the `s-maxage` directive, truncated to whole seconds.
+    pub fn s_maxage(mut self, s_maxage: Duration) -> Self {
+        self.s_maxage = Some(s_maxage);
+        self
+    }
+
+    /// Set the `stale-while-revalidate` directive, truncated to whole seconds.
+    pub fn stale_while_revalidate(mut self, window: Duration) -> Self {
+        self.stale_while_revalidate = Some(window);
+        self
+    }
+
+    /// Set the `immutable` directive.
+    pub fn immutable(mut self) -> Self {
+        self.immutable = true;
+        self
+    }
+}
+
+impl Display for CacheControl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut directives = vec![];
+
+        match self.visibility {
+            Some(Visibility::Public) => directives.push("public".to_string()),
+            Some(Visibility::Private) => directives.push("private".to_string()),
+            None => {}
+        }
+        if self.no_cache {
+            directives.push("no-cache".into());
+        }
+        if self.no_store {
+            directives.push("no-store".into());
+        }
+        if let Some(max_age) = self.max_age {
+            directives.push(format!("max-age={}", max_age.as_secs()));
+        }
+        if let Some(s_maxage) = self.s_maxage {
+            directives.push(format!("s-maxage={}", s_maxage.as_secs()));
+        }
+        if let Some(window) = self.stale_while_revalidate {
+            directives.push(format!("stale-while-revalidate={}", window.as_secs()));
+        }
+        if self.immutable {
+            directives.push("immutable".into());
+        }
+
+        write!(f, "{}", directives.join(", "))
+    }
+}
+
+#[test]
+fn cache_control_directives_are_canonically_ordered() {
+    let cc = CacheControl::new()
+        .immutable()
+        .stale_while_revalidate(Duration::from_secs(30))
+        .s_maxage(Duration::from_secs(600))
+        .max_age(Duration::from_millis(60_500))
+        .no_store()
+        .no_cache()
+        .private()
+        .public();
+
+    assert_eq!(
+        cc.to_string(),
+        "public, no-cache, no-store, max-age=60, s-maxage=600, stale-while-revalidate=30, immutable"
+    );
+    assert_eq!(CacheControl::new().to_string(), "");
+}
@@ -4,7 +4,7 @@ use worker_sys::cf::TlsClientAuth as FfiTlsClientAuth;
 /// In addition to the methods on the `Request` struct, the `Cf` struct on an inbound Request contains information about the request provided by Cloudflare’s edge.
 ///
 /// [Details](https://developers.cloudflare.com/workers/runtime-apis/request#incomingrequestcfproperties)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Cf {
     inner: FfiCf,
 }
@@ -32,6 +32,18 @@ impl Cf {
         self.inner.http_protocol()
     }
 
+    /// The round-trip time in milliseconds of the client's TCP connection to Cloudflare, e.g. `22`.
+    /// Not set for requests which did not arrive over TCP, such as those using HTTP/3.
+    pub fn client_tcp_rtt_ms(&self) -> Option<u32> {
+        self.inner.client_tcp_rtt()
+    }
+
+    /// The original value of the client's `Accept-Encoding` header, e.g. `"gzip, deflate, br"`,
+    /// before Cloudflare normalized it for the request to the Worker.
+    pub fn client_accept_encoding(&self) -> Option<String> {
+        self.inner.client_accept_encoding()
+    }
+
     /// The browser-requested prioritization information in the request object,
     ///
     /// See [this blog post](https://blog.cloudflare.com/better-http-2-prioritization-for-a-faster-web/#customizingprioritizationwithworkers) for details.
@@ -135,6 +147,19 @@ impl Cf {
         self.inner.region_code()
     }
 
+    /// The bot management signals Cloudflare computed for the request. Every field is `None` on
+    /// plans without [Bot Management](https://developers.cloudflare.com/bots/reference/bot-management-variables/).
+    pub fn bot_management(&self) -> BotManagement {
+        match self.inner.bot_management() {
+            Some(bot_management) => BotManagement {
+                score: bot_management.score(),
+                verified_bot: bot_management.verified_bot(),
+                ja3_hash: bot_management.ja3_hash(),
+            },
+            None => BotManagement::default(),
+        }
+    }
+
     /// Timezone of the incoming request
     pub fn timezone(&self) -> impl chrono::TimeZone {
         let tz = self.inner.timezone();
@@ -158,6 +183,19 @@ pub struct RequestPriority {
     pub group_weight: usize,
 }
 
+/// Cloudflare's bot management signals for a request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BotManagement {
+    /// How likely the request is to come from a human, from 1 (almost certainly a bot) to 99.
+    pub score: Option<u32>,
+
+    /// Whether the request comes from a known good bot, such as a search engine crawler.
+    pub verified_bot: Option<bool>,
+
+    /// The [JA3 fingerprint](https://developers.cloudflare.com/bots/concepts/ja3-fingerprint/) of the client's TLS handshake.
+    pub ja3_hash: Option<String>,
+}
+
 impl From<FfiCf> for Cf {
     fn from(inner: FfiCf) -> Self {
         Self { inner }
@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+use crate::{request_init::set_prop, Date, Result};
+
+use js_sys::{Array, Object, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use worker_sys::WorkerGlobalScope;
+
+/// The hash function used to compute an HMAC signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HmacAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HmacAlgorithm {
+    fn hash_name(&self) -> &'static str {
+        match self {
+            HmacAlgorithm::Sha1 => "SHA-1",
+            HmacAlgorithm::Sha256 => "SHA-256",
+            HmacAlgorithm::Sha384 => "SHA-384",
+            HmacAlgorithm::Sha512 => "SHA-512",
+        }
+    }
+}
+
+/// Compute the HMAC signature of `data` using the runtime's WebCrypto implementation.
+pub(crate) async fn hmac_sign(
+    algorithm: HmacAlgorithm,
+    secret: &[u8],
+    data: &[u8],
+) -> Result<Vec<u8>> {
+    let global: WorkerGlobalScope = js_sys::global().unchecked_into();
+    let subtle = global.crypto().subtle();
+
+    let hash = Object::new();
+    set_prop(
+        &hash,
+        &JsValue::from("name"),
+        &JsValue::from(algorithm.hash_name()),
+    );
+    let params = Object::new();
+    set_prop(&params, &JsValue::from("name"), &JsValue::from("HMAC"));
+    set_prop(&params, &JsValue::from("hash"), &hash);
+
+    let usages = Array::of1(&JsValue::from("sign"));
+    let key = subtle.import_key_with_u8_array("raw", secret, &params, false, &usages)?;
+    let key: Object = JsFuture::from(key).await?.unchecked_into();
+
+    let signature = JsFuture::from(subtle.sign_with_u8_array("HMAC", &key, data)?).await?;
+    Ok(Uint8Array::new(&signature).to_vec())
+}
+
+/// Verify the HMAC signature of a webhook `body`, as sent by the provider in `signature_header`,
+/// using the shared `secret`. Signatures are compared in constant time.
+///
+/// The common header formats are supported:
+/// - Stripe's `Stripe-Signature`, e.g. `t=1492774577,v1=5257a8...`, where the signed payload is
+///   the timestamp and body joined by a `.`. Any of the `v1` signatures may match. With a
+///   `tolerance`, signatures whose timestamp is further than it from the current time are
+///   rejected, so that a captured request cannot be replayed later. Stripe uses 5 minutes.
+/// - GitHub's `X-Hub-Signature-256`, e.g. `sha256=757107...`, a hex digest with the name of its
+///   hash as a prefix.
+/// - A bare hex or base64 digest, such as Shopify's `X-Shopify-Hmac-Sha256`. These carry no
+///   timestamp, so `tolerance` does not apply to them.
+///
+/// ```ignore
+/// let body = req.bytes().await?;
+/// let signature = req.headers().get("x-hub-signature-256")?.unwrap_or_default();
+/// let secret = ctx.secret("GITHUB_WEBHOOK_SECRET")?.to_string();
+/// if !verify_signature(&body, &signature, &secret, HmacAlgorithm::Sha256, None).await? {
+///     return Response::error("Unauthorized", 401);
+/// }
+/// ```
+pub async fn verify_signature(
+    body: &[u8],
+    signature_header: &str,
+    secret: &str,
+    algorithm: HmacAlgorithm,
+    tolerance: Option<Duration>,
+) -> Result<bool> {
+    let (payload, signatures) = match parse_signature_header(signature_header) {
+        SignatureHeader::Timestamped {
+            timestamp,
+            signatures,
+        } => {
+            if let Some(tolerance) = tolerance {
+                let now = Date::now().as_millis() / 1000;
+                if !is_within_tolerance(&timestamp, now, tolerance) {
+                    return Ok(false);
+                }
+            }
+            let mut payload = format!("{}.", timestamp).into_bytes();
+            payload.extend_from_slice(body);
+            (payload, signatures)
+        }
+        SignatureHeader::Digest(signature) => (body.to_vec(), vec![signature]),
+    };
+
+    let expected = hmac_sign(algorithm, secret.as_bytes(), &payload).await?;
+    Ok(signatures
+        .iter()
+        .flat_map(|signature| decode_signature(signature))
+        .fold(false, |matched, signature| {
+            constant_time_eq(&signature, &expected) | matched
+        }))
+}
+
+#[derive(Debug, PartialEq)]
+enum SignatureHeader {
+    Timestamped {
+        timestamp: String,
+        signatures: Vec<String>,
+    },
+    Digest(String),
+}
+
+fn parse_signature_header(header: &str) -> SignatureHeader {
+    let mut timestamp = None;
+    let mut signatures = vec![];
+    for (key, value) in header
+        .split(',')
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+    {
+        match key {
+            "t" => timestamp = Some(value.to_string()),
+            "v1" => signatures.push(value.to_string()),
+            _ => {}
+        }
+    }
+
+    match timestamp {
+        Some(timestamp) if !signatures.is_empty() => SignatureHeader::Timestamped {
+            timestamp,
+            signatures,
+        },
+        _ => {
+            let header = header.trim();
+            let digest = ["sha1=", "sha256=", "sha384=", "sha512="]
+                .iter()
+                .find_map(|prefix| header.strip_prefix(prefix))
+                .unwrap_or(header);
+            SignatureHeader::Digest(digest.to_string())
+        }
+    }
+}
+
+/// Whether `timestamp`, in seconds since the Unix epoch, is at most `tolerance` away from `now`.
+fn is_within_tolerance(timestamp: &str, now: u64, tolerance: Duration) -> bool {
+    match timestamp.parse::<u64>() {
+        Ok(timestamp) => timestamp.abs_diff(now) <= tolerance.as_secs(),
+        Err(_) => false,
+    }
+}
+
+fn decode_signature(signature: &str) -> Vec<Vec<u8>> {
+    hex::decode(signature)
+        .into_iter()
+        .chain(base64::decode(signature))
+        .collect()
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+#[test]
+fn signature_headers_are_parsed() {
+    assert_eq!(
+        parse_signature_header("t=1492774577,v1=5257a869,v0=6ffbb59b,v1=aabb"),
+        SignatureHeader::Timestamped {
+            timestamp: "1492774577".into(),
+            signatures: vec!["5257a869".into(), "aabb".into()],
+        }
+    );
+    assert_eq!(
+        parse_signature_header("sha256=757107ea"),
+        SignatureHeader::Digest("757107ea".into())
+    );
+    assert_eq!(
+        parse_signature_header(" OTxmY2E= "),
+        SignatureHeader::Digest("OTxmY2E=".into())
+    );
+    let tolerance = Duration::from_secs(300);
+    assert!(is_within_tolerance("1492774577", 1492774877, tolerance));
+    assert!(!is_within_tolerance("1492774577", 1492774878, tolerance));
+    assert!(!is_within_tolerance("soon", 1492774577, tolerance));
+    assert!(constant_time_eq(b"abc", b"abc"));
+    assert!(!constant_time_eq(b"abc", b"abd"));
+    assert!(!constant_time_eq(b"abc", b"ab"));
+}
@@ -0,0 +1,231 @@
+use crate::{
+    crypto::{constant_time_eq, hmac_sign, HmacAlgorithm},
+    http::Method,
+    middleware::Middleware,
+    request::Request,
+    response::Response,
+    router::RouteContext,
+    Result,
+};
+
+use async_trait::async_trait;
+use worker_sys::global::random_uuid;
+
+#[derive(Debug, Clone)]
+enum CsrfMode {
+    DoubleSubmit,
+    Signed { secret: String },
+}
+
+/// Middleware protecting cookie-authenticated apps against cross-site request forgery. POST, PUT,
+/// PATCH and DELETE requests must submit a token, in a header or a form field, matching the one
+/// stored in a cookie by `Csrf::issue_token`, and are answered with `403 Forbidden` otherwise.
+///
+/// Two modes are supported:
+/// - `Csrf::double_submit`, where the cookie holds the token itself.
+/// - `Csrf::signed`, where the cookie also holds an HMAC-SHA256 signature of the token. With
+///   `Csrf::session_cookie`, the signature also covers the user's session ID, so that a token
+///   issued for another session, e.g. planted by an attacker, is rejected.
+///
+/// The cookie is named `__Host-csrf_token` by default, which browsers only accept when set by the
+/// app's own host, so that it cannot be planted from a sibling subdomain. Renaming it with
+/// `Csrf::cookie_name` gives up that protection unless the new name also starts with `__Host-`.
+///
+/// ```ignore
+/// let csrf = Csrf::signed(&ctx.secret("CSRF_SECRET")?.to_string()).session_cookie("session");
+///
+/// // when rendering a form
+/// let token = csrf.issue_token_for(&session_id).await?;
+/// let mut headers = Headers::new();
+/// headers.set("set-cookie", &token.set_cookie)?;
+/// Response::from_html(render_form(&token.token)).map(|res| res.with_headers(headers))
+///
+/// // when handling it
+/// Router::new().middleware(csrf).post("/settings", update_settings)
+/// ```
+#[derive(Debug, Clone)]
+pub struct Csrf {
+    mode: CsrfMode,
+    cookie_name: String,
+    session_cookie: Option<String>,
+    header_name: String,
+    field_name: String,
+}
+
+/// A CSRF token issued by `Csrf::issue_token`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrfToken {
+    /// The token to submit with state-changing requests, e.g. as a hidden form field.
+    pub token: String,
+    /// The value of the `Set-Cookie` header storing the token with the client.
+    pub set_cookie: String,
+}
+
+impl Csrf {
+    /// Protect requests with a double-submit cookie, which holds the token itself.
+    pub fn double_submit() -> Self {
+        Self::with_mode(CsrfMode::DoubleSubmit)
+    }
+
+    /// Protect requests with a cookie holding the token and its signature using `secret`.
+    pub fn signed(secret: &str) -> Self {
+        Self::with_mode(CsrfMode::Signed {
+            secret: secret.to_string(),
+        })
+    }
+
+    fn with_mode(mode: CsrfMode) -> Self {
+        Self {
+            mode,
+            cookie_name: "__Host-csrf_token".into(),
+            session_cookie: None,
+            header_name: "x-csrf-token".into(),
+            field_name: "csrf_token".into(),
+        }
+    }
+
+    /// Set the name of the cookie storing the token, `__Host-csrf_token` by default.
+    pub fn cookie_name(mut self, name: &str) -> Self {
+        self.cookie_name = name.to_string();
+        self
+    }
+
+    /// Bind signed tokens to the session ID stored in the cookie `name`. Tokens must then be
+    /// issued with `Csrf::issue_token_for`, and requests without the session cookie are rejected.
+    /// Has no effect in double-submit mode.
+    pub fn session_cookie(mut self, name: &str) -> Self {
+        self.session_cookie = Some(name.to_string());
+        self
+    }
+
+    /// Set the name of the header a token can be submitted in, `X-CSRF-Token` by default.
+    pub fn header_name(mut self, name: &str) -> Self {
+        self.header_name = name.to_string();
+        self
+    }
+
+    /// Set the name of the form field a token can be submitted in, `csrf_token` by default.
+    /// Multipart forms are only searched for it up to 64 KiB, so forms uploading files should
+    /// submit the token in the header instead.
+    pub fn field_name(mut self, name: &str) -> Self {
+        self.field_name = name.to_string();
+        self
+    }
+
+    /// Issue a new random token, along with the `Set-Cookie` header value storing it with the
+    /// client. The cookie is `Secure`, `HttpOnly` and `SameSite=Strict`.
+    pub async fn issue_token(&self) -> Result<CsrfToken> {
+        self.issue(None).await
+    }
+
+    /// Issue a new random token like `issue_token`, signed together with `session_id`, the value
+    /// of the cookie set with `Csrf::session_cookie`.
+    pub async fn issue_token_for(&self, session_id: &str) -> Result<CsrfToken> {
+        self.issue(Some(session_id)).await
+    }
+
+    async fn issue(&self, session_id: Option<&str>) -> Result<CsrfToken> {
+        let token = random_uuid();
+        let value = match &self.mode {
+            CsrfMode::DoubleSubmit => token.clone(),
+            CsrfMode::Signed { secret } => {
+                let signature = sign(secret, &signed_message(&token, session_id)).await?;
+                format!("{}.{}", token, signature)
+            }
+        };
+
+        Ok(CsrfToken {
+            set_cookie: format!(
+                "{}={}; Path=/; Secure; HttpOnly; SameSite=Strict",
+                self.cookie_name, value
+            ),
+            token,
+        })
+    }
+
+    async fn is_valid(
+        &self,
+        cookie: &str,
+        session_id: Option<&str>,
+        submitted: &str,
+    ) -> Result<bool> {
+        match &self.mode {
+            CsrfMode::DoubleSubmit => Ok(constant_time_eq(cookie.as_bytes(), submitted.as_bytes())),
+            CsrfMode::Signed { secret } => {
+                let (token, signature) = match cookie.split_once('.') {
+                    Some(parts) => parts,
+                    None => return Ok(false),
+                };
+                if self.session_cookie.is_some() && session_id.is_none() {
+                    return Ok(false);
+                }
+                let expected = sign(secret, &signed_message(token, session_id)).await?;
+
+                Ok(constant_time_eq(signature.as_bytes(), expected.as_bytes())
+                    & constant_time_eq(token.as_bytes(), submitted.as_bytes()))
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<D> Middleware<D> for Csrf {
+    async fn handle(&self, req: &Request, _ctx: &RouteContext<D>) -> Result<Option<Response>> {
+        if !matches!(
+            req.method(),
+            Method::Post | Method::Put | Method::Patch | Method::Delete
+        ) {
+            return Ok(None);
+        }
+
+        let cookies = req.headers().get("cookie")?.unwrap_or_default();
+        let cookie = cookie_value(&cookies, &self.cookie_name);
+        let session_id = match &self.session_cookie {
+            Some(name) => cookie_value(&cookies, name),
+            None => None,
+        };
+        let submitted = match req.headers().get(&self.header_name)? {
+            Some(token) => Some(token),
+            None => req.form_field(&self.field_name).await?,
+        };
+
+        match (cookie, submitted) {
+            (Some(cookie), Some(submitted))
+                if self.is_valid(cookie, session_id, &submitted).await? =>
+            {
+                Ok(None)
+            }
+            _ => Response::error("Forbidden", 403).map(Some),
+        }
+    }
+}
+
+/// The message signed for a token, which is a UUID and so never contains the `.` separator.
+fn signed_message(token: &str, session_id: Option<&str>) -> String {
+    match session_id {
+        Some(session_id) => format!("{}.{}", token, session_id),
+        None => token.to_string(),
+    }
+}
+
+async fn sign(secret: &str, token: &str) -> Result<String> {
+    let signature = hmac_sign(HmacAlgorithm::Sha256, secret.as_bytes(), token.as_bytes()).await?;
+    Ok(hex::encode(signature))
+}
+
+pub(crate) fn cookie_value<'a>(cookies: &'a str, name: &str) -> Option<&'a str> {
+    cookies
+        .split(';')
+        .filter_map(|cookie| cookie.split_once('='))
+        .find(|(key, _)| key.trim() == name)
+        .map(|(_, value)| value.trim())
+}
+
+#[test]
+fn cookie_values_are_found_by_name() {
+    let cookies = "session=abc; csrf_token=1234.feed ;theme=dark";
+    assert_eq!(cookie_value(cookies, "csrf_token"), Some("1234.feed"));
+    assert_eq!(cookie_value(cookies, "theme"), Some("dark"));
+    assert_eq!(cookie_value(cookies, "token"), None);
+    assert_eq!(cookie_value("", "csrf_token"), None);
+}
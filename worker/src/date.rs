@@ -55,6 +55,27 @@ impl Date {
     pub fn as_millis(&self) -> u64 {
         self.js_date.get_time() as u64
     }
+
+    /// Format a Date as an [RFC 1123](https://datatracker.ietf.org/doc/html/rfc7231#section-7.1.1.1)
+    /// HTTP date, as used by headers like `Last-Modified`, e.g. "Thu, 02 Sep 2021 19:38:31 GMT".
+    pub fn to_utc_string(&self) -> String {
+        self.js_date.to_utc_string().into()
+    }
+
+    /// Parse an HTTP date in the [IMF-fixdate](https://datatracker.ietf.org/doc/html/rfc7231#section-7.1.1.1)
+    /// format, e.g. "Thu, 02 Sep 2021 19:38:31 GMT", returning `None` if it is not one. Other
+    /// strings which a JavaScript `Date` would accept, such as "2021", are rejected.
+    pub(crate) fn parse(value: &str) -> Option<Self> {
+        if !is_imf_fixdate(value) {
+            return None;
+        }
+        let date = Date::new(DateInit::String(value.to_string()));
+        if date.js_date.get_time().is_nan() {
+            None
+        } else {
+            Some(date)
+        }
+    }
 }
 
 impl ToString for Date {
@@ -62,3 +83,43 @@ impl ToString for Date {
         self.js_date.to_string().into()
     }
 }
+
+/// Whether `value` has the shape `Www, DD Mon YYYY HH:MM:SS GMT` of an IMF-fixdate. The values
+/// of the fields are checked by the JavaScript parser.
+fn is_imf_fixdate(value: &str) -> bool {
+    const DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let digits = |field: &str| field.bytes().all(|b| b.is_ascii_digit());
+    let bytes = value.as_bytes();
+    value.len() == 29
+        && value.is_ascii()
+        && DAYS.contains(&&value[0..3])
+        && &value[3..5] == ", "
+        && digits(&value[5..7])
+        && bytes[7] == b' '
+        && MONTHS.contains(&&value[8..11])
+        && bytes[11] == b' '
+        && digits(&value[12..16])
+        && bytes[16] == b' '
+        && digits(&value[17..19])
+        && bytes[19] == b':'
+        && digits(&value[20..22])
+        && bytes[22] == b':'
+        && digits(&value[23..25])
+        && &value[25..] == " GMT"
+}
+
+#[test]
+fn only_imf_fixdates_are_accepted() {
+    assert!(is_imf_fixdate("Thu, 02 Sep 2021 19:38:31 GMT"));
+    assert!(!is_imf_fixdate("Thursday, 02-Sep-21 19:38:31 GMT"));
+    assert!(!is_imf_fixdate("Thu, 2 Sep 2021 19:38:31 GMT"));
+    assert!(!is_imf_fixdate("Thu, 02 Sep 2021 19:38:31 UTC"));
+    assert!(!is_imf_fixdate("Thu, 02 Sep 2021 19:38:é GMT"));
+    assert!(!is_imf_fixdate("2021"));
+    assert!(!is_imf_fixdate("Sep 2"));
+    assert!(!is_imf_fixdate("1"));
+}
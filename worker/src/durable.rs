@@ -10,21 +10,28 @@
 //! [Learn more](https://developers.cloudflare.com/workers/learning/using-durable-objects) about
 //! using Durable Objects.
 
-// use std::future::Future;
+use std::cell::RefCell;
+use std::future::Future;
 use std::ops::Deref;
+use std::rc::Rc;
 
 use crate::{
     env::{Env, EnvBinding},
     error::Error,
+    headers::Headers,
+    http::Method,
     request::Request,
+    request_init::RequestInit,
     response::Response,
     Result,
 };
 
 use async_trait::async_trait;
 use js_sys::{Map, Object};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use url::Url;
 use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
 use worker_sys::{
     durable_object::{
         JsObjectId, ObjectNamespace as EdgeObjectNamespace, ObjectState, ObjectStorage, ObjectStub,
@@ -32,8 +39,6 @@ use worker_sys::{
     },
     Response as EdgeResponse,
 };
-// use wasm_bindgen_futures::future_to_promise;
-use wasm_bindgen_futures::JsFuture;
 
 /// A Durable Object stub is a client object used to send requests to a remote Durable Object.
 pub struct Stub {
@@ -54,6 +59,43 @@ impl Stub {
         let response = JsFuture::from(promise).await?;
         Ok(response.dyn_into::<EdgeResponse>()?.into())
     }
+
+    /// Send `body` encoded as JSON in a POST request to `path` on the Durable Object to which the
+    /// stub points, and decode the JSON response as `Res`. A response outside of the 2xx range is
+    /// returned as `Error::Remote`, carrying its status code and body.
+    /// ```ignore
+    /// let total: u64 = stub.call("/add", &Add { amount: 5 }).await?;
+    /// ```
+    pub async fn call<Req: Serialize, Res: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: Req,
+    ) -> Result<Res> {
+        let mut headers = Headers::new();
+        headers.set("content-type", "application/json")?;
+
+        let mut init = RequestInit::new();
+        init.with_method(Method::Post)
+            .with_headers(headers)
+            .with_body(Some(JsValue::from(serde_json::to_string(&body)?)));
+
+        // the host is ignored when fetching from a Durable Object, but a Request needs a full URL
+        let url = match Url::parse(path) {
+            Ok(url) => url.to_string(),
+            Err(_) => format!("https://durable-object/{}", path.trim_start_matches('/')),
+        };
+
+        let mut res = self
+            .fetch_with_request(Request::new_with_init(&url, &init)?)
+            .await?;
+        let status = res.status_code();
+        let text = res.text().await?;
+        if !(200..300).contains(&status) {
+            return Err(Error::Remote { status, body: text });
+        }
+
+        serde_json::from_str(&text).map_err(Error::from)
+    }
 }
 
 /// Use an ObjectNamespace to get access to Stubs for communication with a Durable Object instance.
@@ -183,6 +225,41 @@ impl State {
         }
     }
 
+    /// Run `future` while no other events, such as requests, are delivered to this Durable Object,
+    /// returning its result. Events arriving in the meantime are queued until it completes, so
+    /// persisted state can be loaded on first use without concurrent requests racing to do so.
+    /// ```ignore
+    /// if self.count.is_none() {
+    ///     let storage = self.state.storage();
+    ///     let count: usize = self
+    ///         .state
+    ///         .block_concurrency_while(async move { Ok(storage.get("count").await.unwrap_or(0)) })
+    ///         .await?;
+    ///     self.count = Some(count);
+    /// }
+    /// ```
+    pub async fn block_concurrency_while<T, F>(&self, future: F) -> Result<T>
+    where
+        T: 'static,
+        F: Future<Output = Result<T>> + 'static,
+    {
+        let output = Rc::new(RefCell::new(None));
+        let slot = output.clone();
+        let callback = Closure::once(move || {
+            future_to_promise(async move {
+                let value = future.await.map_err(JsValue::from)?;
+                *slot.borrow_mut() = Some(value);
+                Ok(JsValue::NULL)
+            })
+        });
+
+        JsFuture::from(self.inner.block_concurrency_while_internal(&callback)?).await?;
+        let value = output.borrow_mut().take();
+        value.ok_or_else(|| {
+            Error::RustError("blockConcurrencyWhile did not run its callback".into())
+        })
+    }
+
     // needs to be accessed by the `durable_object` macro in a conversion step
     pub fn _inner(self) -> ObjectState {
         self.inner
@@ -314,36 +391,46 @@ impl Storage {
             .map_err(Error::from)
     }
 
-    // TODO(nilslice): follow up with runtime team on transaction API in general
-    // This function doesn't work on stable yet because the wasm_bindgen `Closure` type is still nightly-gated
-    // #[allow(dead_code)]
-    // async fn transaction<F>(&mut self, closure: fn(Transaction) -> F) -> Result<()>
-    // where
-    //     F: Future<Output = Result<()>> + 'static,
-    // {
-    //     let mut clos = |t: Transaction| {
-    //         future_to_promise(async move {
-    //             closure(t)
-    //                 .await
-    //                 .map_err(JsValue::from)
-    //                 .map(|_| JsValue::NULL)
-    //         })
-    //     };
-    //     JsFuture::from(self.inner.transaction_internal(&mut clos)?)
-    //         .await
-    //         .map_err(Error::from)
-    //         .map(|_| ())
-    // }
+    /// Runs the closure inside an explicit transaction, such that all of its reads and writes are
+    /// atomic and isolated from other storage operations. If the closure returns an error, or calls
+    /// `Transaction::rollback`, none of its writes are committed.
+    /// ```ignore
+    /// storage
+    ///     .transaction(|mut txn| async move {
+    ///         let count: usize = txn.get("count").await.unwrap_or(0);
+    ///         txn.put("count", count + 1).await
+    ///     })
+    ///     .await?;
+    /// ```
+    pub async fn transaction<F, Fut>(&mut self, closure: F) -> Result<()>
+    where
+        F: FnOnce(Transaction) -> Fut + 'static,
+        Fut: Future<Output = Result<()>> + 'static,
+    {
+        let closure = Closure::once(move |txn: ObjectTransaction| {
+            future_to_promise(async move {
+                closure(Transaction { inner: txn })
+                    .await
+                    .map_err(JsValue::from)
+                    .map(|_| JsValue::NULL)
+            })
+        });
+        JsFuture::from(self.inner.transaction_internal(&closure)?)
+            .await
+            .map_err(Error::from)
+            .map(|_| ())
+    }
 }
 
-#[allow(dead_code)]
-struct Transaction {
+/// The storage available within `Storage::transaction`. Operations on a `Transaction` are
+/// committed together once the transaction's closure completes successfully.
+pub struct Transaction {
     inner: ObjectTransaction,
 }
 
-#[allow(dead_code)]
 impl Transaction {
-    async fn get<T: for<'a> Deserialize<'a>>(&self, key: &str) -> Result<T> {
+    /// Retrieves the value associated with the given key within this transaction.
+    pub async fn get<T: for<'a> Deserialize<'a>>(&self, key: &str) -> Result<T> {
         JsFuture::from(self.inner.get_internal(key)?)
             .await
             .and_then(|val| {
@@ -356,7 +443,8 @@ impl Transaction {
             .map_err(Error::from)
     }
 
-    async fn get_multiple(&self, keys: Vec<impl Deref<Target = str>>) -> Result<Map> {
+    /// Retrieves the values associated with each of the provided keys within this transaction.
+    pub async fn get_multiple(&self, keys: Vec<impl Deref<Target = str>>) -> Result<Map> {
         let keys = self.inner.get_multiple_internal(
             keys.into_iter()
                 .map(|key| JsValue::from(key.deref()))
@@ -366,15 +454,16 @@ impl Transaction {
         keys.dyn_into::<Map>().map_err(Error::from)
     }
 
-    async fn put<T: Serialize>(&mut self, key: &str, value: T) -> Result<()> {
+    /// Stores the value and associates it with the given key within this transaction.
+    pub async fn put<T: Serialize>(&mut self, key: &str, value: T) -> Result<()> {
         JsFuture::from(self.inner.put_internal(key, JsValue::from_serde(&value)?)?)
             .await
             .map_err(Error::from)
             .map(|_| ())
     }
 
-    // Each key-value pair in the serialized object will be added to the storage
-    async fn put_multiple<T: Serialize>(&mut self, values: T) -> Result<()> {
+    /// Takes a serializable struct and stores each of its keys and values within this transaction.
+    pub async fn put_multiple<T: Serialize>(&mut self, values: T) -> Result<()> {
         let values = JsValue::from_serde(&values)?;
         if !values.is_object() {
             return Err("Must pass in a struct type".to_string().into());
@@ -385,7 +474,9 @@ impl Transaction {
             .map(|_| ())
     }
 
-    async fn delete(&mut self, key: &str) -> Result<bool> {
+    /// Deletes the key and associated value within this transaction. Returns true if the key
+    /// existed or false if it didn't.
+    pub async fn delete(&mut self, key: &str) -> Result<bool> {
         let fut: JsFuture = self.inner.delete_internal(key)?.into();
         fut.await
             .and_then(|jsv| {
@@ -395,7 +486,9 @@ impl Transaction {
             .map_err(Error::from)
     }
 
-    async fn delete_multiple(&mut self, keys: Vec<impl Deref<Target = str>>) -> Result<usize> {
+    /// Deletes the provided keys and their associated values within this transaction. Returns a
+    /// count of the number of key-value pairs deleted.
+    pub async fn delete_multiple(&mut self, keys: Vec<impl Deref<Target = str>>) -> Result<usize> {
         let fut: JsFuture = self
             .inner
             .delete_multiple_internal(
@@ -413,19 +506,23 @@ impl Transaction {
             .map_err(Error::from)
     }
 
-    async fn delete_all(&mut self) -> Result<()> {
+    /// Deletes all keys and associated values within this transaction.
+    pub async fn delete_all(&mut self) -> Result<()> {
         let fut: JsFuture = self.inner.delete_all_internal()?.into();
         fut.await.map(|_| ()).map_err(Error::from)
     }
 
-    async fn list(&self) -> Result<Map> {
+    /// Returns all keys and values visible to this transaction in ascending lexicographic order.
+    pub async fn list(&self) -> Result<Map> {
         let fut: JsFuture = self.inner.list_internal()?.into();
         fut.await
             .and_then(|jsv| jsv.dyn_into())
             .map_err(Error::from)
     }
 
-    async fn list_with_options(&self, opts: ListOptions<'_>) -> Result<Map> {
+    /// Returns keys visible to this transaction according to the parameters in the provided
+    /// options object.
+    pub async fn list_with_options(&self, opts: ListOptions<'_>) -> Result<Map> {
         let fut: JsFuture = self
             .inner
             .list_with_options_internal(JsValue::from_serde(&opts)?.into())?
@@ -435,7 +532,8 @@ impl Transaction {
             .map_err(Error::from)
     }
 
-    fn rollback(&mut self) -> Result<()> {
+    /// Aborts the transaction, discarding all of its writes once the closure completes.
+    pub fn rollback(&mut self) -> Result<()> {
         self.inner.rollback_internal().map_err(Error::from)
     }
 }
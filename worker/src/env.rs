@@ -1,19 +1,33 @@
+use crate::analytics_engine::AnalyticsEngine;
 use crate::durable::ObjectNamespace;
 use crate::error::Error;
+use crate::hyperdrive::Hyperdrive;
+use crate::kv::{self, ByteStream, GetOptions, GetType, KvGetValue};
+use crate::service::Service;
 use crate::Result;
 
 use js_sys::Object;
 use wasm_bindgen::{prelude::*, JsCast, JsValue};
 use worker_kv::KvStore;
 
+// R2 buckets share most of these methods, but not `getWithMetadata`
+const KV_METHODS: &[&str] = &["get", "getWithMetadata", "put", "list", "delete"];
+
 #[wasm_bindgen]
 extern "C" {
     /// Env contains any bindings you have associated with the Worker when you uploaded it.
+    #[derive(Clone)]
     pub type Env;
 }
 
 impl Env {
-    fn get_binding<T: EnvBinding>(&self, name: &str) -> Result<T> {
+    /// Access the raw JavaScript object of any binding, by the name configured for it in your
+    /// wrangler.toml file.
+    ///
+    /// This is an escape hatch for bindings which this crate does not wrap yet: the object can be
+    /// used through your own `wasm-bindgen` imports until a typed wrapper is available. Prefer the
+    /// typed accessors, such as `kv` or `durable_object`, where they exist.
+    pub fn get_binding(&self, name: &str) -> Result<JsValue> {
         // Weird rust-analyzer bug is causing it to think Reflect::get is unsafe
         #[allow(unused_unsafe)]
         let binding = unsafe { js_sys::Reflect::get(self, &JsValue::from(name)) }
@@ -23,35 +37,215 @@ impl Env {
                 .to_string()
                 .into())
         } else {
-            // Can't just use JsCast::dyn_into here because the type name might not be in scope
-            // resulting in a terribly annoying javascript error which can't be caught
-            T::get(binding)
+            Ok(binding)
+        }
+    }
+
+    fn typed_binding<T: EnvBinding>(&self, name: &str) -> Result<T> {
+        // Can't just use JsCast::dyn_into here because the type name might not be in scope
+        // resulting in a terribly annoying javascript error which can't be caught
+        let binding = self.get_binding(name)?;
+        T::get(binding.clone()).map_err(|_| Error::BindingTypeMismatch {
+            name: name.to_string(),
+            expected: T::TYPE_NAME,
+            found: binding_type(&binding),
+        })
+    }
+
+    /// Get the binding named `name`, checking that it has each of `members`, for bindings whose
+    /// class name is not exposed consistently by the runtime. This catches a binding of the wrong
+    /// type, e.g. a Durable Object namespace bound where a KV namespace was expected, before it
+    /// fails with a JavaScript exception on first use.
+    fn duck_typed_binding(
+        &self,
+        name: &str,
+        expected: &'static str,
+        members: &[&str],
+    ) -> Result<JsValue> {
+        let binding = self.get_binding(name)?;
+        let has_members = binding.is_object()
+            && members.iter().all(|member| {
+                // Weird rust-analyzer bug is causing it to think Reflect::get is unsafe
+                #[allow(unused_unsafe)]
+                unsafe { js_sys::Reflect::get(&binding, &JsValue::from(*member)) }
+                    .map(|value| !value.is_undefined())
+                    .unwrap_or(false)
+            });
+
+        if has_members {
+            Ok(binding)
+        } else {
+            Err(Error::BindingTypeMismatch {
+                name: name.to_string(),
+                expected,
+                found: binding_type(&binding),
+            })
         }
     }
 
+    /// Whether a binding with the given name is present on this Worker, regardless of its type.
+    pub fn has_binding(&self, name: &str) -> bool {
+        // Weird rust-analyzer bug is causing it to think Reflect::get is unsafe
+        #[allow(unused_unsafe)]
+        unsafe { js_sys::Reflect::get(self, &JsValue::from(name)) }
+            .map(|binding| !binding.is_undefined())
+            .unwrap_or(false)
+    }
+
     /// Access Secret value bindings added to your Worker via the UI or `wrangler`:
     /// <https://developers.cloudflare.com/workers/cli-wrangler/commands#secret>
     pub fn secret(&self, binding: &str) -> Result<Secret> {
-        self.get_binding::<Secret>(binding)
+        self.typed_binding::<Secret>(binding)
+    }
+
+    /// Access a Secret value binding holding binary key material, such as a signing key for use
+    /// with WebCrypto. Secrets can only be stored as strings, so the bytes must be base64-encoded
+    /// when the secret is added, e.g. `openssl rand -base64 32 | wrangler secret put SIGNING_KEY`.
+    /// Both the standard and URL-safe alphabets are accepted, with or without padding.
+    pub fn secret_bytes(&self, binding: &str) -> Result<Vec<u8>> {
+        decode_secret(&self.secret(binding)?.to_string())
+            .ok_or_else(|| Error::RustError(format!("secret `{}` is not valid base64", binding)))
     }
 
     /// Environment variables are defined via the `[vars]` configuration in your wrangler.toml file
     /// and are always plaintext values.
     pub fn var(&self, binding: &str) -> Result<Var> {
-        self.get_binding::<Var>(binding)
+        self.typed_binding::<Var>(binding)
     }
 
     /// Access a Workers KV namespace by the binding name configured in your wrangler.toml file.
     pub fn kv(&self, binding: &str) -> Result<KvStore> {
+        self.duck_typed_binding(binding, "KV namespace", KV_METHODS)?;
         KvStore::from_this(&self, binding).map_err(From::from)
     }
 
+    /// Read a value from the Workers KV namespace bound as `binding`, with control over how long
+    /// it may be cached at the edge and how it is decoded. Returns `None` if the key does not
+    /// exist. `kv(binding)?.get(key)` is equivalent to the default `GetOptions`.
+    /// ```ignore
+    /// let options = GetOptions {
+    ///     cache_ttl: Some(3600),
+    ///     get_type: GetType::Json,
+    /// };
+    /// if let Some(KvGetValue::Json(config)) = env.kv_get_with_options("CONFIG", "flags", &options).await? {
+    ///     // ...
+    /// }
+    /// ```
+    pub async fn kv_get_with_options(
+        &self,
+        binding: &str,
+        key: &str,
+        options: &GetOptions,
+    ) -> Result<Option<KvGetValue>> {
+        let namespace = self.duck_typed_binding(binding, "KV namespace", KV_METHODS)?;
+        kv::get_with_options(&namespace, key, options).await
+    }
+
+    /// Read a value from the Workers KV namespace bound as `binding` as a stream of bytes, so
+    /// that large values can be forwarded without buffering them in memory. Returns `None` if the
+    /// key does not exist.
+    /// ```ignore
+    /// match env.kv_get_stream("ASSETS", "video.mp4").await? {
+    ///     Some(stream) => Response::from_stream(stream),
+    ///     None => Response::error("Not Found", 404),
+    /// }
+    /// ```
+    pub async fn kv_get_stream(&self, binding: &str, key: &str) -> Result<Option<ByteStream>> {
+        let options = GetOptions {
+            get_type: GetType::Stream,
+            ..GetOptions::default()
+        };
+
+        match self.kv_get_with_options(binding, key, &options).await? {
+            Some(KvGetValue::Stream(stream)) => Ok(Some(stream)),
+            Some(_) => unreachable!("KV values read with `GetType::Stream` are streams"),
+            None => Ok(None),
+        }
+    }
+
+    /// Read a binary value, such as an image or a protobuf message, from the Workers KV namespace
+    /// bound as `binding`. Returns `None` if the key does not exist. Values stored as bytes are
+    /// corrupted when read as text with `kv(binding)?.get(key)`.
+    pub async fn kv_get_bytes(&self, binding: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        let options = GetOptions {
+            get_type: GetType::ArrayBuffer,
+            ..GetOptions::default()
+        };
+
+        match self.kv_get_with_options(binding, key, &options).await? {
+            Some(KvGetValue::ArrayBuffer(bytes)) => Ok(Some(bytes)),
+            Some(_) => unreachable!("KV values read with `GetType::ArrayBuffer` are bytes"),
+            None => Ok(None),
+        }
+    }
+
+    /// Access a Workers Analytics Engine dataset by the binding name configured in your
+    /// wrangler.toml file.
+    pub fn analytics_engine(&self, binding: &str) -> Result<AnalyticsEngine> {
+        let dataset: worker_sys::AnalyticsEngineDataset = self
+            .duck_typed_binding(binding, "Analytics Engine dataset", &["writeDataPoint"])?
+            .unchecked_into();
+        Ok(dataset.into())
+    }
+
+    /// Access a service binding to another Worker by the binding name configured in your
+    /// wrangler.toml file.
+    pub fn service(&self, binding: &str) -> Result<Service> {
+        // RPC bindings are proxies whose class name is not `Fetcher`, so they are checked by the
+        // method every service binding has
+        let fetcher: worker_sys::Fetcher = self
+            .duck_typed_binding(binding, "service binding", &["fetch"])?
+            .unchecked_into();
+        Ok(fetcher.into())
+    }
+
+    /// Access a Hyperdrive configuration by the binding name configured in your wrangler.toml
+    /// file.
+    pub fn hyperdrive(&self, binding: &str) -> Result<Hyperdrive> {
+        let hyperdrive: worker_sys::Hyperdrive = self
+            .duck_typed_binding(binding, "Hyperdrive configuration", &["connectionString"])?
+            .unchecked_into();
+        Ok(hyperdrive.into())
+    }
+
     /// Access a Durable Object namespace by the binding name configured in your wrangler.toml file.
     pub fn durable_object(&self, binding: &str) -> Result<ObjectNamespace> {
-        self.get_binding(binding)
+        self.typed_binding(binding)
     }
 }
 
+/// Describe the type of `binding` for a `BindingTypeMismatch` error, by its class name.
+fn binding_type(binding: &JsValue) -> String {
+    if binding.is_string() {
+        return "String".into();
+    }
+    if !binding.is_object() {
+        return binding
+            .js_typeof()
+            .as_string()
+            .unwrap_or_else(|| "value".into());
+    }
+
+    let name: String = Object::from(binding.clone()).constructor().name().into();
+    if name.is_empty() {
+        "Object".into()
+    } else {
+        name
+    }
+}
+
+fn decode_secret(secret: &str) -> Option<Vec<u8>> {
+    let secret = secret.trim();
+    [
+        base64::STANDARD,
+        base64::URL_SAFE,
+        base64::STANDARD_NO_PAD,
+        base64::URL_SAFE_NO_PAD,
+    ]
+    .iter()
+    .find_map(|config| base64::decode_config(secret, *config).ok())
+}
+
 pub trait EnvBinding: Sized + JsCast {
     const TYPE_NAME: &'static str;
 
@@ -113,3 +307,11 @@ impl ToString for StringBinding {
 pub type Secret = StringBinding;
 /// A string value representing a binding to an environment variable in a Worker.
 pub type Var = StringBinding;
+
+#[test]
+fn binary_secrets_are_decoded_from_base64() {
+    let key = [0xfb, 0xff, 0x00, 0x10];
+    assert_eq!(decode_secret("+/8AEA==\n"), Some(key.to_vec()));
+    assert_eq!(decode_secret("-_8AEA"), Some(key.to_vec()));
+    assert_eq!(decode_secret("not base64!"), None);
+}
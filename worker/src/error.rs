@@ -5,15 +5,36 @@ use wasm_bindgen::{JsCast, JsValue};
 #[non_exhaustive]
 pub enum Error {
     BadEncoding,
+    BodyTooLarge {
+        limit: u64,
+    },
     BodyUsed,
+    ContentLengthMismatch {
+        expected: u64,
+        actual: u64,
+    },
     Json((String, u16)),
     JsError(String),
     Internal(JsValue),
+    InvalidUtf8,
+    Remote {
+        status: u16,
+        body: String,
+    },
     BindingError(String),
+    BindingTypeMismatch {
+        name: String,
+        expected: &'static str,
+        found: String,
+    },
     RouteInsertError(matchit::InsertError),
+    RouteRegistration(crate::router::RouteRegistrationError),
     RouteNoDataError,
     RustError(String),
+    Serialization(String),
     SerdeJsonError(serde_json::Error),
+    TrailersUnsupported,
+    UnsupportedMediaType,
 }
 
 impl From<worker_kv::KvError> for Error {
@@ -33,14 +54,39 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::BadEncoding => write!(f, "content-type mismatch"),
+            Error::BodyTooLarge { limit } => write!(f, "body exceeds the limit of {} bytes", limit),
             Error::BodyUsed => write!(f, "body has already been read"),
+            Error::ContentLengthMismatch { expected, actual } => write!(
+                f,
+                "body length {} does not match content-length {}",
+                actual, expected
+            ),
             Error::Json((msg, status)) => write!(f, "{} (status: {})", msg, status),
             Error::JsError(s) | Error::RustError(s) => write!(f, "{}", s),
             Error::Internal(_) => write!(f, "unrecognized JavaScript object"),
+            Error::InvalidUtf8 => write!(f, "body is not valid UTF-8"),
+            Error::Remote { status, body } => {
+                write!(f, "remote responded with status {}: {}", status, body)
+            }
             Error::BindingError(name) => write!(f, "no binding found for `{}`", name),
+            Error::BindingTypeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "binding `{}` is a {}, not a {}; check its type in wrangler.toml",
+                name, found, expected
+            ),
             Error::RouteInsertError(e) => write!(f, "failed to insert route: {}", e),
+            Error::RouteRegistration(e) => write!(f, "{}", e),
             Error::RouteNoDataError => write!(f, "route has no corresponding shared data"),
+            Error::Serialization(msg) => write!(f, "{}", msg),
             Error::SerdeJsonError(e) => write!(f, "Serde Error: {}", e),
+            Error::UnsupportedMediaType => write!(f, "unsupported content-type"),
+            Error::TrailersUnsupported => {
+                write!(f, "the Workers runtime cannot send response trailers")
+            }
         }
     }
 }
@@ -5,9 +5,9 @@ use crate::Date;
 use crate::DateInit;
 use crate::Result;
 
-use worker_sys::{File as EdgeFile, FormData as EdgeFormData};
+use worker_sys::{file::FilePropertyBag, File as EdgeFile, FormData as EdgeFormData};
 
-use js_sys::Array;
+use js_sys::{Array, Uint8Array};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
@@ -92,6 +92,54 @@ impl FormData {
     pub fn delete(&mut self, name: &str) {
         self.0.delete(name)
     }
+
+    /// Append a field to this `FormData`, returning it for chaining. Used to build outbound
+    /// `multipart/form-data` bodies, see `FormData::append_file`.
+    pub fn append_field(mut self, name: &str, value: &str) -> Result<Self> {
+        self.append(name, value)?;
+        Ok(self)
+    }
+
+    /// Append a file to this `FormData` with the given filename and content type, returning it
+    /// for chaining. Converting the `FormData` into a `JsValue` makes it a request body, which
+    /// the runtime encodes as `multipart/form-data` with the matching `Content-Type` header.
+    /// ```ignore
+    /// let form = FormData::new()
+    ///     .append_field("description", "Quarterly report")?
+    ///     .append_file("file", "report.pdf", "application/pdf", &bytes)?;
+    ///
+    /// let mut init = RequestInit::new();
+    /// init.with_method(Method::Post).with_body(Some(form.into()));
+    /// ```
+    pub fn append_file(
+        self,
+        name: &str,
+        filename: &str,
+        content_type: &str,
+        bytes: &[u8],
+    ) -> Result<Self> {
+        let mut options = FilePropertyBag::new();
+        options.type_(content_type);
+        let bits = Array::of1(&Uint8Array::from(bytes));
+        let file = EdgeFile::new_with_u8_array_sequence_and_options(&bits, filename, &options)?;
+
+        self.0
+            .append_with_blob_and_filename(name, &file, filename)
+            .map_err(Error::from)?;
+        Ok(self)
+    }
+}
+
+impl Default for FormData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<FormData> for JsValue {
+    fn from(form_data: FormData) -> Self {
+        form_data.0.into()
+    }
 }
 
 impl From<JsValue> for FormData {
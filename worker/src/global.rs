@@ -1,4 +1,6 @@
-use crate::{request::Request as WorkerRequest, response::Response as WorkerResponse, Result};
+use crate::{
+    error::Error, request::Request as WorkerRequest, response::Response as WorkerResponse, Result,
+};
 use worker_sys::{Response as EdgeResponse, WorkerGlobalScope};
 
 use wasm_bindgen::JsCast;
@@ -21,6 +23,31 @@ impl Fetch {
     }
 }
 
+/// Send `req`, following up to `max_hops` redirects in Rust rather than leaving them to the
+/// runtime, as described by `Request::follow_redirect`. Returns the first response which is not a
+/// redirect, or an error if there are more than `max_hops` redirects.
+/// ```ignore
+/// let req = Request::new("https://example.com/old-path", Method::Get)?;
+/// let res = fetch_follow_redirects(req, 5).await?;
+/// ```
+pub async fn fetch_follow_redirects(req: WorkerRequest, max_hops: usize) -> Result<WorkerResponse> {
+    let mut req = req.with_manual_redirect()?;
+    for _ in 0..=max_hops {
+        // keep an unsent copy, whose body can be sent again for a 307 or 308 redirect
+        let next = req.clone()?;
+        let res = fetch_with_request(&req).await?;
+        match next.follow_redirect(&res)? {
+            Some(next) => req = next,
+            None => return Ok(res),
+        }
+    }
+
+    Err(Error::RustError(format!(
+        "exceeded the maximum of {} redirects",
+        max_hops
+    )))
+}
+
 async fn fetch_with_str(url: &str) -> Result<WorkerResponse> {
     let worker: WorkerGlobalScope = js_sys::global().unchecked_into();
     let promise = worker.fetch_with_str(url);
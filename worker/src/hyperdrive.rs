@@ -0,0 +1,55 @@
+use worker_sys::Hyperdrive as EdgeHyperdrive;
+
+/// A [Hyperdrive](https://developers.cloudflare.com/hyperdrive/) binding, which provides the
+/// credentials of a pooled connection to a Postgres or MySQL database. Pass them to a database
+/// driver which connects over the Workers sockets API.
+/// ```ignore
+/// let hyperdrive = ctx.hyperdrive("DB")?;
+/// let client = connect(&hyperdrive.connection_string()).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Hyperdrive {
+    inner: EdgeHyperdrive,
+}
+
+impl Hyperdrive {
+    /// The URL to connect to the database through Hyperdrive, including the credentials below.
+    pub fn connection_string(&self) -> String {
+        self.inner.connection_string()
+    }
+
+    /// The host to connect to, which is local to the Worker rather than the database's own host.
+    pub fn host(&self) -> String {
+        self.inner.host()
+    }
+
+    /// The port to connect to.
+    pub fn port(&self) -> u16 {
+        self.inner.port()
+    }
+
+    /// The user to connect as.
+    pub fn user(&self) -> String {
+        self.inner.user()
+    }
+
+    /// The password to connect with, generated by Hyperdrive rather than the database's own.
+    pub fn password(&self) -> String {
+        self.inner.password()
+    }
+
+    /// The name of the database to connect to.
+    pub fn database(&self) -> String {
+        self.inner.database()
+    }
+
+    pub fn inner(&self) -> &EdgeHyperdrive {
+        &self.inner
+    }
+}
+
+impl From<EdgeHyperdrive> for Hyperdrive {
+    fn from(inner: EdgeHyperdrive) -> Self {
+        Self { inner }
+    }
+}
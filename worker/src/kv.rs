@@ -0,0 +1,415 @@
+use std::{future::Future, pin::Pin};
+
+use crate::{request_init::set_prop, response::serialization_error, Error, Result};
+
+use async_trait::async_trait;
+use futures::{future::try_join_all, Stream, StreamExt};
+use js_sys::{ArrayBuffer, Object, Uint8Array};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use wasm_streams::ReadableStream;
+use worker_kv::{KvError, KvStore};
+
+/// The maximum number of KV operations issued concurrently by the bulk helpers, keeping a single
+/// invocation well within the Workers [subrequest limits](https://developers.cloudflare.com/workers/platform/limits#kv).
+const KV_BATCH_SIZE: usize = 25;
+
+/// The shortest expiration TTL, in seconds, which KV accepts.
+const KV_MIN_TTL: u64 = 60;
+
+/// Additional operations on a Workers KV namespace, built on top of the `KvStore` binding.
+#[async_trait(?Send)]
+pub trait KvStoreExt {
+    /// Deletes each of the provided keys. Deletes are issued concurrently in batches, and the first
+    /// failure is returned once its batch has settled.
+    async fn delete_many(&self, keys: &[&str]) -> Result<()>;
+
+    /// Deletes every key beginning with the prefix, paging through the namespace listing. Returns
+    /// the number of keys removed.
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64>;
+
+    /// Reads the value of `key` along with its version, as stored by `put_with_version`. Values
+    /// written without a version have version 0. Returns `None` if the key does not exist.
+    async fn get_with_version(&self, key: &str) -> Result<Option<(String, u64)>>;
+
+    /// Writes `value` to `key` as the next version, if the version currently stored is
+    /// `expected`, as read with `get_with_version`. `None` expects the key not to exist. Returns
+    /// the new version, or `None` without writing if the stored version is different, in which
+    /// case the caller should read the value again and retry.
+    ///
+    /// **Note:** KV has no atomic compare-and-set, so this only narrows the window for lost
+    /// updates rather than closing it. The version is checked by a read before the write, and
+    /// two writers passing the check in between both succeed, with the last write winning. As KV
+    /// is eventually consistent, writes made in other locations can also take up to 60 seconds to
+    /// be seen by the check. Use a Durable Object where every update must be kept.
+    /// ```ignore
+    /// let current = kv.get_with_version("config").await?;
+    /// let updated = update(current.as_ref().map(|(config, _)| config));
+    /// let expected = current.map(|(_, version)| version);
+    /// if kv.put_with_version("config", &updated, expected).await?.is_none() {
+    ///     // someone else updated the config first
+    /// }
+    /// ```
+    async fn put_with_version(
+        &self,
+        key: &str,
+        value: &str,
+        expected: Option<u64>,
+    ) -> Result<Option<u64>>;
+
+    /// Lists the keys in the namespace matching `options`, one page at a time. Pass the cursor of
+    /// a response which is not `list_complete` to `KvListOptions::cursor` to fetch the next page.
+    /// ```ignore
+    /// let page = kv
+    ///     .list_with(KvListOptions::new().prefix("user:").limit(100))
+    ///     .await?;
+    /// for key in &page.keys {
+    ///     let metadata: Option<UserMetadata> = key.metadata()?;
+    /// }
+    /// ```
+    async fn list_with(&self, options: KvListOptions) -> Result<KvListResponse>;
+}
+
+#[derive(Serialize, Deserialize)]
+struct VersionMetadata {
+    version: u64,
+}
+
+#[async_trait(?Send)]
+impl KvStoreExt for KvStore {
+    async fn delete_many(&self, keys: &[&str]) -> Result<()> {
+        for batch in keys.chunks(KV_BATCH_SIZE) {
+            try_join_all(batch.iter().map(|key| self.delete(key))).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64> {
+        let mut deleted = 0;
+        let mut cursor = None;
+
+        loop {
+            let mut list = self.list().prefix(prefix.to_string());
+            if let Some(cursor) = cursor.take() {
+                list = list.cursor(cursor);
+            }
+            let page = list.execute().await?;
+
+            let names: Vec<&str> = page.keys.iter().map(|key| key.name.as_str()).collect();
+            self.delete_many(&names).await?;
+            deleted += names.len() as u64;
+
+            match page.cursor {
+                Some(next) if !page.list_complete => cursor = Some(next),
+                _ => break,
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    async fn get_with_version(&self, key: &str) -> Result<Option<(String, u64)>> {
+        match self.get_with_metadata::<VersionMetadata>(key).await {
+            Ok(Some((value, metadata))) => Ok(Some((value.as_string(), metadata.version))),
+            Ok(None) => Ok(None),
+            // a key which does not exist, or was written without metadata
+            Err(KvError::InvalidMetadata(_)) => {
+                Ok(self.get(key).await?.map(|value| (value.as_string(), 0)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn put_with_version(
+        &self,
+        key: &str,
+        value: &str,
+        expected: Option<u64>,
+    ) -> Result<Option<u64>> {
+        let current = self
+            .get_with_version(key)
+            .await?
+            .map(|(_, version)| version);
+        if current != expected {
+            return Ok(None);
+        }
+
+        let version = expected.map_or(1, |version| version + 1);
+        self.put(key, value)?
+            .metadata(VersionMetadata { version })?
+            .execute()
+            .await?;
+        Ok(Some(version))
+    }
+
+    async fn list_with(&self, options: KvListOptions) -> Result<KvListResponse> {
+        let mut list = self.list();
+        if let Some(prefix) = options.prefix {
+            list = list.prefix(prefix);
+        }
+        if let Some(limit) = options.limit {
+            list = list.limit(limit);
+        }
+        if let Some(cursor) = options.cursor {
+            list = list.cursor(cursor);
+        }
+        let page = list.execute().await?;
+
+        Ok(KvListResponse {
+            keys: page
+                .keys
+                .into_iter()
+                .map(|key| KvKey {
+                    name: key.name,
+                    expiration: key.expiration,
+                    metadata: key.metadata,
+                })
+                .collect(),
+            // the runtime may send an empty cursor with the last page
+            cursor: page.cursor.filter(|cursor| !cursor.is_empty()),
+            list_complete: page.list_complete,
+        })
+    }
+}
+
+/// Options for listing the keys in a KV namespace with `KvStoreExt::list_with`, see the
+/// [KV docs](https://developers.cloudflare.com/workers/runtime-apis/kv#listing-keys).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KvListOptions {
+    prefix: Option<String>,
+    limit: Option<u64>,
+    cursor: Option<String>,
+}
+
+impl KvListOptions {
+    /// Create a new `KvListOptions` listing every key, 1000 at a time.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only list the keys beginning with `prefix`.
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// List at most `limit` keys. The default is 1000, which is also the maximum.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Continue listing after the page which returned `cursor`.
+    pub fn cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+}
+
+/// A page of keys listed with `KvStoreExt::list_with`.
+#[derive(Debug, Clone)]
+pub struct KvListResponse {
+    /// The keys in this page, ordered lexicographically.
+    pub keys: Vec<KvKey>,
+    /// Whether this is the last page, in which case there is no cursor.
+    pub list_complete: bool,
+    /// The cursor to pass to `KvListOptions::cursor` for the next page.
+    pub cursor: Option<String>,
+}
+
+/// A key listed with `KvStoreExt::list_with`.
+#[derive(Debug, Clone)]
+pub struct KvKey {
+    name: String,
+    expiration: Option<u64>,
+    metadata: Option<Value>,
+}
+
+impl KvKey {
+    /// The name of the key.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// When the key expires, as a [unix timestamp](https://en.wikipedia.org/wiki/Unix_time) in
+    /// seconds, if it was written with an expiration.
+    pub fn expiration(&self) -> Option<u64> {
+        self.expiration
+    }
+
+    /// Decode the metadata the key was written with, or `None` if it has none. Returns
+    /// `Error::SerdeJsonError` if the metadata is not a valid `M`.
+    pub fn metadata<M: DeserializeOwned>(&self) -> Result<Option<M>> {
+        match &self.metadata {
+            Some(metadata) => Ok(Some(serde_json::from_value(metadata.clone())?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// A cache of JSON values in a Workers KV namespace, for the common pattern of computing a value
+/// once and keeping it in KV for a while.
+/// ```ignore
+/// let cache = JsonCache::new(ctx.kv("CACHE")?);
+/// let user: User = cache
+///     .get_or_insert_with(&format!("user:{}", id), 300, || fetch_user(id))
+///     .await?;
+/// ```
+pub struct JsonCache {
+    store: KvStore,
+}
+
+impl JsonCache {
+    /// Construct a `JsonCache` storing its values in the given namespace.
+    pub fn new(store: KvStore) -> Self {
+        Self { store }
+    }
+
+    /// Get the value cached for `key`, or run `compute` and cache its result for `ttl` seconds.
+    /// Returns `Error::RustError` without touching KV if `ttl` is under KV's minimum of 60
+    /// seconds. Other errors from KV are returned as they are, while values which cannot be
+    /// encoded as JSON return `Error::Serialization`, and cached values which cannot be decoded as
+    /// `T` return `Error::SerdeJsonError`.
+    pub async fn get_or_insert_with<T, F, Fut>(&self, key: &str, ttl: u64, compute: F) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if ttl < KV_MIN_TTL {
+            return Err(Error::RustError(format!(
+                "KV expiration TTL must be at least {} seconds, got {}",
+                KV_MIN_TTL, ttl
+            )));
+        }
+
+        if let Some(cached) = self.store.get(key).await? {
+            return serde_json::from_str(&cached.as_string()).map_err(Error::from);
+        }
+
+        let value = compute().await?;
+        let json = serde_json::to_string(&value).map_err(serialization_error)?;
+        self.store
+            .put(key, json)?
+            .expiration_ttl(ttl)
+            .execute()
+            .await?;
+
+        Ok(value)
+    }
+}
+
+/// How a KV value is decoded when read with `Env::kv_get_with_options`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GetType {
+    #[default]
+    Text,
+    Json,
+    ArrayBuffer,
+    Stream,
+}
+
+impl From<GetType> for &str {
+    fn from(get_type: GetType) -> Self {
+        match get_type {
+            GetType::Text => "text",
+            GetType::Json => "json",
+            GetType::ArrayBuffer => "arrayBuffer",
+            GetType::Stream => "stream",
+        }
+    }
+}
+
+/// Options for reading a KV value, see the [KV docs](https://developers.cloudflare.com/workers/runtime-apis/kv#reading-key-value-pairs).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GetOptions {
+    /// How many seconds the value may be cached at the edge location which read it. Must be at
+    /// least 60. Longer TTLs make repeated reads faster, at the cost of seeing updates later.
+    pub cache_ttl: Option<u64>,
+    /// How the value is decoded, as text by default.
+    pub get_type: GetType,
+}
+
+/// A stream of bytes read from the runtime, such as a KV value read with `GetType::Stream`. It can
+/// be passed directly to `Response::from_stream`.
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>>>>>;
+
+/// A KV value, decoded according to the `GetType` it was read with.
+pub enum KvGetValue {
+    Text(String),
+    Json(Value),
+    ArrayBuffer(Vec<u8>),
+    Stream(ByteStream),
+}
+
+pub(crate) async fn get_with_options(
+    namespace: &JsValue,
+    key: &str,
+    options: &GetOptions,
+) -> Result<Option<KvGetValue>> {
+    let opts = Object::new();
+    set_prop(
+        &opts,
+        &JsValue::from("type"),
+        &JsValue::from(<&str>::from(options.get_type)),
+    );
+    if let Some(cache_ttl) = options.cache_ttl {
+        set_prop(
+            &opts,
+            &JsValue::from("cacheTtl"),
+            &JsValue::from(cache_ttl as f64),
+        );
+    }
+
+    #[allow(unused_unsafe)]
+    let get: js_sys::Function = unsafe { js_sys::Reflect::get(namespace, &JsValue::from("get")) }?
+        .dyn_into()
+        .map_err(|_| Error::JsError("KV namespace does not have a `get` method".into()))?;
+    let promise: js_sys::Promise = get
+        .call2(namespace, &JsValue::from(key), &opts)?
+        .unchecked_into();
+    let value = JsFuture::from(promise).await?;
+    if value.is_null() || value.is_undefined() {
+        return Ok(None);
+    }
+
+    let value = match options.get_type {
+        GetType::Text => KvGetValue::Text(value.as_string().unwrap_or_default()),
+        GetType::Json => {
+            let json = js_sys::JSON::stringify(&value)?;
+            KvGetValue::Json(serde_json::from_str(&String::from(json))?)
+        }
+        GetType::ArrayBuffer => KvGetValue::ArrayBuffer(
+            Uint8Array::new(&value.unchecked_into::<ArrayBuffer>()).to_vec(),
+        ),
+        GetType::Stream => {
+            let chunks = ReadableStream::from_raw(value.unchecked_into())
+                .into_stream()
+                .map(|chunk| Ok(Uint8Array::new(&chunk?).to_vec()));
+            KvGetValue::Stream(Box::pin(chunks))
+        }
+    };
+
+    Ok(Some(value))
+}
+
+#[test]
+fn listed_key_metadata_is_decoded() {
+    let key = KvKey {
+        name: "user:1".into(),
+        expiration: None,
+        metadata: Some(serde_json::json!({ "version": 3 })),
+    };
+    let metadata: Option<VersionMetadata> = key.metadata().unwrap();
+    assert_eq!(metadata.map(|metadata| metadata.version), Some(3));
+    assert!(key.metadata::<u64>().is_err());
+
+    let key = KvKey {
+        metadata: None,
+        ..key
+    };
+    assert!(key.metadata::<VersionMetadata>().unwrap().is_none());
+}
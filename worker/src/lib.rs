@@ -1,4 +1,9 @@
+mod abort;
+mod analytics_engine;
+mod cache_control;
 mod cf;
+mod crypto;
+mod csrf;
 mod date;
 pub mod durable;
 mod env;
@@ -7,30 +12,64 @@ mod formdata;
 mod global;
 mod headers;
 mod http;
+mod hyperdrive;
+mod kv;
+mod metrics;
+mod middleware;
+mod mime;
 mod request;
 mod request_init;
 mod response;
 mod router;
+mod service;
+mod session;
+mod single_flight;
+mod sse;
+pub mod status;
+pub mod time;
+mod transform_stream;
 
 #[doc(hidden)]
 use std::result::Result as StdResult;
 
 pub type Result<T> = StdResult<T, error::Error>;
 
+pub use crate::abort::{AbortController, AbortFuture, AbortSignal};
+pub use crate::analytics_engine::{AnalyticsDataPoint, AnalyticsEngine};
+pub use crate::cache_control::CacheControl;
+pub use crate::crypto::{verify_signature, HmacAlgorithm};
+pub use crate::csrf::{Csrf, CsrfToken};
 pub use crate::date::{Date, DateInit};
 pub use crate::env::Env;
 pub use crate::error::Error;
 pub use crate::formdata::*;
-pub use crate::global::Fetch;
+pub use crate::global::{fetch_follow_redirects, Fetch};
 pub use crate::headers::Headers;
 pub use crate::http::Method;
-pub use crate::request::Request;
+pub use crate::hyperdrive::Hyperdrive;
+pub use crate::kv::{
+    ByteStream, GetOptions, GetType, JsonCache, KvGetValue, KvKey, KvListOptions, KvListResponse,
+    KvStoreExt,
+};
+pub use crate::metrics::{Counter, Gauge, Histogram, Metrics};
+pub use crate::middleware::{
+    check_origin, redirect_to_https, CheckOrigin, Middleware, RedirectToHttps,
+};
+pub use crate::mime::{mime_for_extension, mime_for_path, ContentEncoding, ContentType};
+pub use crate::request::{JsonOptions, Request};
 pub use crate::request_init::*;
-pub use crate::response::Response;
-pub use crate::router::{RouteContext, RouteParams, Router};
-pub use cf::Cf;
+pub use crate::response::{JsonResponseOptions, Response};
+pub use crate::router::{
+    fetch_router, Limits, RouteContext, RouteError, RouteParams, RouteRegistrationError, Router,
+};
+pub use crate::service::Service;
+pub use crate::session::{Session, Sessions};
+pub use crate::single_flight::SingleFlight;
+pub use crate::sse::SseEvent;
+pub use crate::transform_stream::TransformStream;
+pub use cf::{BotManagement, Cf};
 
-pub use worker_sys::console_log;
+pub use worker_sys::{console_error, console_log};
 
 pub use crate::durable::*;
 pub use worker_macros::{durable_object, event};
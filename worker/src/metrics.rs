@@ -0,0 +1,375 @@
+use std::cell::{Cell, RefCell};
+use std::fmt::Write;
+use std::rc::Rc;
+
+type Labels = Vec<(String, String)>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl Kind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Counter => "counter",
+            Kind::Gauge => "gauge",
+            Kind::Histogram => "histogram",
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Series {
+    Value(Rc<Cell<f64>>),
+    Histogram(Rc<RefCell<HistogramState>>),
+}
+
+struct Family {
+    name: String,
+    help: String,
+    kind: Kind,
+    series: Vec<(Labels, Series)>,
+}
+
+#[derive(Debug)]
+struct HistogramState {
+    bounds: Vec<f64>,
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+/// A registry of counters, gauges and histograms, rendered in the Prometheus
+/// [text exposition format](https://prometheus.io/docs/instrumenting/exposition_formats/) by
+/// `Response::from_metrics`, e.g. from a `/metrics` route.
+///
+/// **Note:** metrics accumulate in the memory of a single isolate, and are lost whenever it is
+/// evicted. Each isolate serving a Worker reports only the requests it handled itself, so to
+/// aggregate metrics across them, write data points to Analytics Engine as well.
+/// ```ignore
+/// let requests = Metrics::global().counter_with_labels(
+///     "http_requests_total",
+///     "Requests handled, by method.",
+///     &[("method", &req.method().to_string())],
+/// );
+/// requests.inc();
+///
+/// router.get("/metrics", |_, _| Response::from_metrics(&Metrics::global()))
+/// ```
+#[derive(Clone, Default)]
+pub struct Metrics {
+    families: Rc<RefCell<Vec<Family>>>,
+}
+
+thread_local! {
+    static GLOBAL: Metrics = Metrics::new();
+}
+
+impl Metrics {
+    /// Construct an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the registry shared by everything running in this isolate.
+    pub fn global() -> Self {
+        GLOBAL.with(Metrics::clone)
+    }
+
+    /// Get the counter named `name`, registering it with the `help` text if it does not exist.
+    ///
+    /// Panics if `name` is already registered as another kind of metric, as do the other
+    /// accessors.
+    pub fn counter(&self, name: &str, help: &str) -> Counter {
+        self.counter_with_labels(name, help, &[])
+    }
+
+    /// Get the counter named `name` with the given labels, registering it if it does not exist.
+    /// Counters with the same name and different labels are reported together.
+    pub fn counter_with_labels(&self, name: &str, help: &str, labels: &[(&str, &str)]) -> Counter {
+        match self.series(name, help, Kind::Counter, labels, &[]) {
+            Series::Value(value) => Counter { value },
+            Series::Histogram(_) => unreachable!("counters are stored as values"),
+        }
+    }
+
+    /// Get the gauge named `name`, registering it with the `help` text if it does not exist.
+    pub fn gauge(&self, name: &str, help: &str) -> Gauge {
+        self.gauge_with_labels(name, help, &[])
+    }
+
+    /// Get the gauge named `name` with the given labels, registering it if it does not exist.
+    pub fn gauge_with_labels(&self, name: &str, help: &str, labels: &[(&str, &str)]) -> Gauge {
+        match self.series(name, help, Kind::Gauge, labels, &[]) {
+            Series::Value(value) => Gauge { value },
+            Series::Histogram(_) => unreachable!("gauges are stored as values"),
+        }
+    }
+
+    /// Get the histogram named `name`, registering it with the `help` text and the upper bounds
+    /// of its `buckets` if it does not exist. The `+Inf` bucket is always added.
+    pub fn histogram(&self, name: &str, help: &str, buckets: &[f64]) -> Histogram {
+        self.histogram_with_labels(name, help, buckets, &[])
+    }
+
+    /// Get the histogram named `name` with the given labels, registering it if it does not exist.
+    pub fn histogram_with_labels(
+        &self,
+        name: &str,
+        help: &str,
+        buckets: &[f64],
+        labels: &[(&str, &str)],
+    ) -> Histogram {
+        match self.series(name, help, Kind::Histogram, labels, buckets) {
+            Series::Histogram(state) => Histogram { state },
+            Series::Value(_) => unreachable!("histograms are stored as histograms"),
+        }
+    }
+
+    fn series(
+        &self,
+        name: &str,
+        help: &str,
+        kind: Kind,
+        labels: &[(&str, &str)],
+        buckets: &[f64],
+    ) -> Series {
+        let labels: Labels = labels
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        let mut families = self.families.borrow_mut();
+        let family = match families.iter().position(|family| family.name == name) {
+            Some(i) => &mut families[i],
+            None => {
+                families.push(Family {
+                    name: name.to_string(),
+                    help: help.to_string(),
+                    kind,
+                    series: Vec::new(),
+                });
+                families.last_mut().unwrap()
+            }
+        };
+        if family.kind != kind {
+            panic!(
+                "metric `{}` is already registered as a {}",
+                name,
+                family.kind.as_str()
+            );
+        }
+
+        if let Some((_, series)) = family.series.iter().find(|(l, _)| *l == labels) {
+            return series.clone();
+        }
+        let series = match kind {
+            Kind::Histogram => {
+                let mut bounds = buckets.to_vec();
+                bounds.retain(|bound| bound.is_finite());
+                bounds.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                bounds.dedup();
+                Series::Histogram(Rc::new(RefCell::new(HistogramState {
+                    counts: vec![0; bounds.len()],
+                    bounds,
+                    sum: 0.0,
+                    count: 0,
+                })))
+            }
+            _ => Series::Value(Rc::new(Cell::new(0.0))),
+        };
+        family.series.push((labels, series.clone()));
+        series
+    }
+
+    /// Render every registered metric in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for family in self.families.borrow().iter() {
+            let _ = writeln!(out, "# HELP {} {}", family.name, escape_help(&family.help));
+            let _ = writeln!(out, "# TYPE {} {}", family.name, family.kind.as_str());
+
+            for (labels, series) in &family.series {
+                match series {
+                    Series::Value(value) => {
+                        write_sample(&mut out, &family.name, labels, None, value.get())
+                    }
+                    Series::Histogram(state) => {
+                        let state = state.borrow();
+                        let bucket = format!("{}_bucket", family.name);
+                        let mut cumulative = 0;
+                        for (bound, count) in state.bounds.iter().zip(&state.counts) {
+                            cumulative += count;
+                            let le = format_value(*bound);
+                            write_sample(&mut out, &bucket, labels, Some(&le), cumulative as f64);
+                        }
+                        write_sample(&mut out, &bucket, labels, Some("+Inf"), state.count as f64);
+                        let sum = format!("{}_sum", family.name);
+                        write_sample(&mut out, &sum, labels, None, state.sum);
+                        let count = format!("{}_count", family.name);
+                        write_sample(&mut out, &count, labels, None, state.count as f64);
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// A metric which only goes up, such as the number of requests handled.
+#[derive(Debug, Clone)]
+pub struct Counter {
+    value: Rc<Cell<f64>>,
+}
+
+impl Counter {
+    /// Increment the counter by 1.
+    pub fn inc(&self) {
+        self.inc_by(1.0)
+    }
+
+    /// Increment the counter by `amount`. Negative amounts are ignored, since counters can only go
+    /// up.
+    pub fn inc_by(&self, amount: f64) {
+        if amount > 0.0 {
+            self.value.set(self.value.get() + amount);
+        }
+    }
+
+    /// The current value of the counter.
+    pub fn get(&self) -> f64 {
+        self.value.get()
+    }
+}
+
+/// A metric which can go up and down, such as the number of requests in flight.
+#[derive(Debug, Clone)]
+pub struct Gauge {
+    value: Rc<Cell<f64>>,
+}
+
+impl Gauge {
+    /// Set the gauge to `value`.
+    pub fn set(&self, value: f64) {
+        self.value.set(value);
+    }
+
+    /// Increment the gauge by 1.
+    pub fn inc(&self) {
+        self.add(1.0)
+    }
+
+    /// Decrement the gauge by 1.
+    pub fn dec(&self) {
+        self.add(-1.0)
+    }
+
+    /// Add `amount` to the gauge, which may be negative.
+    pub fn add(&self, amount: f64) {
+        self.value.set(self.value.get() + amount);
+    }
+
+    /// The current value of the gauge.
+    pub fn get(&self) -> f64 {
+        self.value.get()
+    }
+}
+
+/// A metric counting observations in buckets, such as the duration of requests.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    state: Rc<RefCell<HistogramState>>,
+}
+
+impl Histogram {
+    /// Record an observation of `value`.
+    pub fn observe(&self, value: f64) {
+        let mut state = self.state.borrow_mut();
+        if let Some(i) = state.bounds.iter().position(|bound| value <= *bound) {
+            state.counts[i] += 1;
+        }
+        state.sum += value;
+        state.count += 1;
+    }
+}
+
+fn write_sample(out: &mut String, name: &str, labels: &Labels, le: Option<&str>, value: f64) {
+    let mut pairs: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label(value)))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{}\"", le));
+    }
+
+    if pairs.is_empty() {
+        let _ = writeln!(out, "{} {}", name, format_value(value));
+    } else {
+        let _ = writeln!(
+            out,
+            "{}{{{}}} {}",
+            name,
+            pairs.join(","),
+            format_value(value)
+        );
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".into()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.into()
+    } else {
+        value.to_string()
+    }
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn escape_label(value: &str) -> String {
+    escape_help(value).replace('"', "\\\"")
+}
+
+#[test]
+fn metrics_are_rendered_in_the_text_format() {
+    let metrics = Metrics::new();
+    metrics
+        .counter_with_labels("requests_total", "Requests.", &[("status", "200")])
+        .inc_by(2.0);
+    metrics
+        .counter_with_labels("requests_total", "Requests.", &[("status", "200")])
+        .inc();
+    metrics
+        .counter_with_labels("requests_total", "Requests.", &[("path", "/a\"b")])
+        .inc();
+    metrics.gauge("in_flight", "In flight.").dec();
+
+    let latency = metrics.histogram("latency_seconds", "Latency.", &[0.5, 0.1]);
+    latency.observe(0.05);
+    latency.observe(0.3);
+    latency.observe(2.0);
+
+    assert_eq!(
+        metrics.render(),
+        "# HELP requests_total Requests.\n\
+         # TYPE requests_total counter\n\
+         requests_total{status=\"200\"} 3\n\
+         requests_total{path=\"/a\\\"b\"} 1\n\
+         # HELP in_flight In flight.\n\
+         # TYPE in_flight gauge\n\
+         in_flight -1\n\
+         # HELP latency_seconds Latency.\n\
+         # TYPE latency_seconds histogram\n\
+         latency_seconds_bucket{le=\"0.1\"} 1\n\
+         latency_seconds_bucket{le=\"0.5\"} 2\n\
+         latency_seconds_bucket{le=\"+Inf\"} 3\n\
+         latency_seconds_sum 2.35\n\
+         latency_seconds_count 3\n"
+    );
+}
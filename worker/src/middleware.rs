@@ -0,0 +1,146 @@
+use crate::{
+    error::Error, http::Method, request::Request, response::Response, router::RouteContext, Result,
+};
+
+use async_trait::async_trait;
+
+/// Middleware runs before the handler of each route it applies to, and may respond in place of
+/// the handler by returning `Some(Response)`. Returning `None` passes the request on to the next
+/// middleware, and finally to the handler.
+///
+/// Any `fn(&Request, &RouteContext<D>) -> Result<Option<Response>>` is a `Middleware`; implement
+/// this trait directly for middleware which needs configuration or `async/await`.
+/// ```ignore
+/// fn require_auth(req: &Request, _ctx: &RouteContext<()>) -> Result<Option<Response>> {
+///     if req.headers().has("authorization")? {
+///         return Ok(None);
+///     }
+///     Response::error("Unauthorized", 401).map(Some)
+/// }
+///
+/// Router::new()
+///     .get("/", index)
+///     .group("/admin", |admin| admin.middleware(require_auth).get("/users", list_users))
+/// ```
+#[async_trait(?Send)]
+pub trait Middleware<D> {
+    async fn handle(&self, req: &Request, ctx: &RouteContext<D>) -> Result<Option<Response>>;
+}
+
+#[async_trait(?Send)]
+impl<D, F> Middleware<D> for F
+where
+    F: Fn(&Request, &RouteContext<D>) -> Result<Option<Response>>,
+{
+    async fn handle(&self, req: &Request, ctx: &RouteContext<D>) -> Result<Option<Response>> {
+        (self)(req, ctx)
+    }
+}
+
+/// Middleware which redirects requests sent over plain HTTP to the same URL over HTTPS, with a
+/// `301 Moved Permanently`. Requests already sent over HTTPS, as detected by `Request::is_secure`,
+/// are passed on unchanged. Created by calling `redirect_to_https`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RedirectToHttps;
+
+/// Create a `RedirectToHttps` middleware.
+/// ```ignore
+/// Router::new().middleware(redirect_to_https()).get("/", index)
+/// ```
+pub fn redirect_to_https() -> RedirectToHttps {
+    RedirectToHttps
+}
+
+#[async_trait(?Send)]
+impl<D> Middleware<D> for RedirectToHttps {
+    async fn handle(&self, req: &Request, _ctx: &RouteContext<D>) -> Result<Option<Response>> {
+        if req.is_secure() {
+            return Ok(None);
+        }
+
+        let mut url = req.url()?;
+        url.set_scheme("https")
+            .map_err(|_| Error::RustError(format!("cannot redirect {} to https", url)))?;
+        Response::moved_permanently(url).map(Some)
+    }
+}
+
+/// Middleware which rejects state-changing requests from other sites with `403 Forbidden`, as a
+/// lightweight defense against cross-site request forgery. `POST`, `PUT`, `PATCH` and `DELETE`
+/// requests must carry an `Origin` header, or failing that a `Referer` header, naming one of the
+/// allowed hosts, while requests with any other method are passed on unchanged. Created by
+/// calling `check_origin`.
+///
+/// Browsers send `Origin` with every cross-origin request and every `POST`, so this protects
+/// browser clients without tokens. Non-browser clients which send neither header are rejected too.
+/// See `Csrf` for token-based protection.
+#[derive(Debug, Clone, Default)]
+pub struct CheckOrigin {
+    allowed_hosts: Vec<String>,
+}
+
+/// Create a `CheckOrigin` middleware, allowing requests from the given hosts, e.g.
+/// `"example.com"`, or `"localhost:8787"` to require a port. Hosts are compared
+/// case-insensitively.
+/// ```ignore
+/// Router::new()
+///     .middleware(check_origin(&["example.com", "www.example.com"]))
+///     .post("/settings", update_settings)
+/// ```
+pub fn check_origin(allowed_hosts: &[&str]) -> CheckOrigin {
+    CheckOrigin {
+        allowed_hosts: allowed_hosts
+            .iter()
+            .map(|host| host.to_ascii_lowercase())
+            .collect(),
+    }
+}
+
+impl CheckOrigin {
+    fn allows(&self, source: Option<&str>) -> bool {
+        let url = match source.and_then(|source| url::Url::parse(source).ok()) {
+            Some(url) => url,
+            None => return false,
+        };
+        let host = match url.host_str() {
+            Some(host) => host.to_ascii_lowercase(),
+            None => return false,
+        };
+
+        self.allowed_hosts.iter().any(|allowed| {
+            *allowed == host
+                || matches!(url.port(), Some(port) if *allowed == format!("{}:{}", host, port))
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl<D> Middleware<D> for CheckOrigin {
+    async fn handle(&self, req: &Request, _ctx: &RouteContext<D>) -> Result<Option<Response>> {
+        if !matches!(
+            req.method(),
+            Method::Post | Method::Put | Method::Patch | Method::Delete
+        ) {
+            return Ok(None);
+        }
+
+        let source = req.origin().or_else(|| req.referrer());
+        if self.allows(source.as_deref()) {
+            return Ok(None);
+        }
+
+        Response::error("Forbidden", 403).map(Some)
+    }
+}
+
+#[test]
+fn origins_are_checked_against_allowed_hosts() {
+    let check = check_origin(&["Example.com", "localhost:8787"]);
+    assert!(check.allows(Some("https://example.com")));
+    assert!(check.allows(Some("https://EXAMPLE.com/settings?tab=1")));
+    assert!(check.allows(Some("http://localhost:8787")));
+    assert!(!check.allows(Some("http://localhost:3000")));
+    assert!(!check.allows(Some("https://example.com.evil.net")));
+    assert!(!check.allows(Some("null")));
+    assert!(!check.allows(None));
+}
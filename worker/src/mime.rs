@@ -0,0 +1,226 @@
+/// Common values for a `Content-Type` header, used with `Response::with_content_type`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentType {
+    Json,
+    Html,
+    Text,
+    OctetStream,
+    Css,
+    JavaScript,
+    FormUrlEncoded,
+    Custom(String),
+}
+
+impl ContentType {
+    /// The MIME type for this `ContentType`, as used in a `Content-Type` header.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ContentType::Json => "application/json",
+            ContentType::Html => "text/html",
+            ContentType::Text => "text/plain",
+            ContentType::OctetStream => "application/octet-stream",
+            ContentType::Css => "text/css",
+            ContentType::JavaScript => "text/javascript",
+            ContentType::FormUrlEncoded => "application/x-www-form-urlencoded",
+            ContentType::Custom(mime) => mime,
+        }
+    }
+}
+
+impl std::fmt::Display for ContentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The encodings a response body can be compressed with, as named in the `Accept-Encoding` and
+/// `Content-Encoding` headers. See `Response::compress`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncoding {
+    Brotli,
+    Gzip,
+    Deflate,
+}
+
+impl ContentEncoding {
+    /// The name of this encoding, as used in a `Content-Encoding` header.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentEncoding::Brotli => "br",
+            ContentEncoding::Gzip => "gzip",
+            ContentEncoding::Deflate => "deflate",
+        }
+    }
+}
+
+/// Choose the encoding among `available` which `accept_encoding`, the value of an
+/// `Accept-Encoding` header, gives the highest quality. Ties go to the encoding listed first in
+/// `available`, and encodings the header does not accept, explicitly or through `*`, are never
+/// chosen.
+// `Option::is_none_or` would need Rust 1.82
+#[allow(clippy::unnecessary_map_or)]
+pub(crate) fn negotiate_encoding(
+    accept_encoding: &str,
+    available: &[ContentEncoding],
+) -> Option<ContentEncoding> {
+    let mut qualities = Vec::new();
+    for coding in accept_encoding.split(',') {
+        let mut params = coding.split(';');
+        let name = params
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        if name.is_empty() {
+            continue;
+        }
+        let quality = params
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        qualities.push((name, quality));
+    }
+    let quality_of = |name: &str| {
+        qualities
+            .iter()
+            .find(|(coding, _)| coding == name)
+            .or_else(|| qualities.iter().find(|(coding, _)| coding == "*"))
+            .map(|(_, quality)| *quality)
+            .unwrap_or(0.0)
+    };
+
+    let mut best: Option<(ContentEncoding, f32)> = None;
+    for encoding in available {
+        let quality = quality_of(encoding.as_str());
+        if quality > 0.0 && best.map_or(true, |(_, best)| quality > best) {
+            best = Some((*encoding, quality));
+        }
+    }
+    best.map(|(encoding, _)| encoding)
+}
+
+/// The media type of a `Content-Type` header, lowercased and without its parameters.
+pub(crate) fn media_type(content_type: Option<&str>) -> String {
+    let content_type = content_type.unwrap_or_default();
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Whether a body of the given `Content-Type` is worth compressing: text, and text-based formats
+/// such as JSON, JavaScript, XML and SVG. Images, archives and fonts are already compressed, so
+/// compressing them again only costs time.
+pub(crate) fn is_compressible(content_type: &str) -> bool {
+    let media_type = media_type(Some(content_type));
+    media_type.starts_with("text/")
+        || media_type.ends_with("+json")
+        || media_type.ends_with("+xml")
+        || matches!(
+            media_type.as_str(),
+            "application/json"
+                | "application/javascript"
+                | "application/ecmascript"
+                | "application/xml"
+                | "application/wasm"
+                | "application/x-www-form-urlencoded"
+        )
+}
+
+/// Infer the MIME type of a file from its extension, with or without a leading `.`, compared
+/// case-insensitively. Unknown extensions are `application/octet-stream`.
+pub fn mime_for_extension(ext: &str) -> &'static str {
+    match ext.trim_start_matches('.').to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" | "map" => "application/json",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        "xml" => "application/xml",
+        "md" => "text/markdown",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "webmanifest" => "application/manifest+json",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Infer the MIME type of a file from the extension of its path, e.g. `/assets/app.css`. Paths
+/// without an extension are `application/octet-stream`.
+pub fn mime_for_path(path: &str) -> &'static str {
+    let file_name = path.rsplit('/').next().unwrap_or_default();
+    match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => mime_for_extension(ext),
+        _ => "application/octet-stream",
+    }
+}
+
+#[test]
+fn mime_types_are_inferred_from_paths() {
+    assert_eq!(mime_for_extension("WOFF2"), "font/woff2");
+    assert_eq!(mime_for_extension(".wasm"), "application/wasm");
+    assert_eq!(mime_for_path("/assets/app.min.js"), "text/javascript");
+    assert_eq!(mime_for_path("/index.html"), "text/html");
+    assert_eq!(
+        mime_for_path("/.well-known/.env"),
+        "application/octet-stream"
+    );
+    assert_eq!(mime_for_path("/v1.2/README"), "application/octet-stream");
+}
+
+#[test]
+fn encodings_are_negotiated_by_quality() {
+    use ContentEncoding::*;
+    let all = [Brotli, Gzip, Deflate];
+
+    assert_eq!(negotiate_encoding("gzip, deflate, br", &all), Some(Brotli));
+    assert_eq!(negotiate_encoding("br;q=0.5, gzip", &all), Some(Gzip));
+    assert_eq!(negotiate_encoding("deflate, *;q=0.1", &all), Some(Deflate));
+    assert_eq!(negotiate_encoding("br;q=0, identity", &all), None);
+    assert_eq!(negotiate_encoding("", &all), None);
+    assert_eq!(negotiate_encoding("br", &[Gzip]), None);
+}
+
+#[test]
+fn only_text_based_types_are_compressible() {
+    assert!(is_compressible("text/html; charset=utf-8"));
+    assert!(is_compressible("Application/JSON"));
+    assert!(is_compressible("image/svg+xml"));
+    assert!(is_compressible("application/manifest+json"));
+    assert!(!is_compressible("image/png"));
+    assert!(!is_compressible("application/zip"));
+    assert!(!is_compressible("font/woff2"));
+    assert!(!is_compressible(""));
+}
+
+#[test]
+fn media_types_ignore_parameters_and_case() {
+    assert_eq!(
+        media_type(Some("Application/JSON; charset=utf-8")),
+        "application/json"
+    );
+    assert_eq!(
+        media_type(Some(" multipart/form-data; boundary=x")),
+        "multipart/form-data"
+    );
+    assert_eq!(media_type(None), "");
+}
@@ -1,10 +1,48 @@
-use crate::{cf::Cf, error::Error, headers::Headers, http::Method, FormData, RequestInit, Result};
+use crate::{
+    cf::Cf,
+    error::Error,
+    headers::Headers,
+    http::Method,
+    mime::media_type,
+    response::{Response, ResponseInit},
+    FormData, FormEntry, RequestCredentials, RequestInit, RequestMode, RequestRedirect, Result,
+};
 
-use js_sys;
+use futures::StreamExt;
+use js_sys::{self, Uint8Array};
 use serde::de::DeserializeOwned;
+use serde_json::Value;
 use url::Url;
+use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
-use worker_sys::{Request as EdgeRequest, RequestInit as EdgeRequestInit};
+use wasm_streams::ReadableStream;
+use worker_sys::{
+    Request as EdgeRequest, RequestInit as EdgeRequestInit, Response as EdgeResponse,
+};
+
+/// The largest multipart body read to find a single form field, e.g. a CSRF token, so that file
+/// uploads are not buffered for it.
+const FORM_FIELD_MULTIPART_LIMIT: u64 = 64 * 1024;
+
+/// Options for decoding a JSON request body with `Request::json_with_options`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// Reject object fields which are not part of the decoded type.
+    pub deny_unknown_fields: bool,
+    /// The maximum nesting depth of arrays and objects, where a scalar has a depth of 0.
+    pub max_depth: Option<usize>,
+}
+
+impl JsonOptions {
+    /// Options which reject unknown fields and nest no deeper than 32 levels, as used by
+    /// `Request::json_strict`.
+    pub fn strict() -> Self {
+        Self {
+            deny_unknown_fields: true,
+            max_depth: Some(32),
+        }
+    }
+}
 
 /// A [Request](https://developer.mozilla.org/en-US/docs/Web/API/Request) representation for
 /// handling incoming and creating outbound HTTP requests.
@@ -15,6 +53,7 @@ pub struct Request {
     cf: Cf,
     edge_request: EdgeRequest,
     body_used: bool,
+    body_limit: Option<u64>,
     immutable: bool,
 }
 
@@ -35,6 +74,7 @@ impl From<EdgeRequest> for Request {
             cf: req.cf().into(),
             edge_request: req,
             body_used: false,
+            body_limit: None,
             immutable: true,
         }
     }
@@ -73,47 +113,290 @@ impl Request {
             })
     }
 
+    /// Set the body of this `Request`, replacing any body it already had. Bodies cannot be sent
+    /// with `GET` or `HEAD` requests.
+    /// ```ignore
+    /// let req = Request::new("https://example.com/items", Method::Post)?
+    ///     .with_body(Some("{\"name\":\"widget\"}".into()))?
+    ///     .with_headers(headers)?;
+    /// let res = Fetch::Request(req).send().await?;
+    /// ```
+    pub fn with_body(self, body: Option<JsValue>) -> Result<Self> {
+        let mut init = EdgeRequestInit::new();
+        init.body(body.as_ref());
+        self.with_init(&init)
+    }
+
+    /// Set the headers of this `Request`, replacing any headers it already had.
+    pub fn with_headers(self, headers: Headers) -> Result<Self> {
+        let mut init = EdgeRequestInit::new();
+        init.headers(headers.as_ref());
+        self.with_init(&init)
+    }
+
+    fn with_init(self, init: &EdgeRequestInit) -> Result<Self> {
+        let req = EdgeRequest::new_with_request_and_init(&self.edge_request, init)?;
+        let mut req: Request = req.into();
+        req.body_limit = self.body_limit;
+        req.immutable = false;
+        Ok(req)
+    }
+
+    /// Create an outbound copy of this `Request` sent to `url`, keeping its method, headers and
+    /// redirect mode. The body is handed over as a stream without being read into Rust, so
+    /// combined with `Fetch`, whose responses are returned to the client as streams too, a proxy
+    /// keeps memory flat regardless of body size. The body limit set with `set_body_limit` is not
+    /// enforced on a forwarded body.
+    /// ```ignore
+    /// let upstream = req.forward_to("https://origin.example.com/api")?;
+    /// Fetch::Request(upstream).send().await
+    /// ```
+    pub fn forward_to(self, url: &str) -> Result<Self> {
+        if self.body_used {
+            return Err(Error::BodyUsed);
+        }
+
+        // a Request is a valid RequestInit for another request, which takes over its body stream
+        let init: &EdgeRequestInit = self.edge_request.unchecked_ref();
+        let mut req: Request = EdgeRequest::new_with_str_and_init(url, init)?.into();
+        req.immutable = false;
+        Ok(req)
+    }
+
+    /// Create a copy of this `Request` sent to `new_url`, keeping its method, headers, redirect
+    /// mode and body. Unlike `forward_to`, this `Request` remains usable: the body is split into
+    /// two streams, so it is not read into Rust but is buffered by the runtime for whichever copy
+    /// reads it more slowly.
+    pub fn clone_with_url(&self, new_url: &str) -> Result<Self> {
+        if self.body_used {
+            return Err(Error::BodyUsed);
+        }
+
+        let copy = self.edge_request.clone()?;
+        let init: &EdgeRequestInit = copy.unchecked_ref();
+        let mut req: Request = EdgeRequest::new_with_str_and_init(new_url, init)?.into();
+        req.immutable = false;
+        Ok(req)
+    }
+
+    /// Build the request which follows `res`, the response this `Request` received, if it is a
+    /// `301`, `302`, `303`, `307` or `308` redirect with a `Location` header. Call it on an unsent
+    /// copy of the request, as its body is handed over to the next request.
+    ///
+    /// `307` and `308` redirects keep the method and body, while the others switch any method but
+    /// `GET` and `HEAD` to a `GET` without a body. The `Authorization` and `Cookie` headers are
+    /// dropped when redirected to another origin. The next request uses `RequestRedirect::Manual`,
+    /// so it can be followed in turn; see `fetch_follow_redirects`.
+    pub fn follow_redirect(self, res: &Response) -> Result<Option<Self>> {
+        let status = res.status_code();
+        if !matches!(status, 301 | 302 | 303 | 307 | 308) {
+            return Ok(None);
+        }
+        let location = match res.headers().get("location")? {
+            Some(location) => location,
+            None => return Ok(None),
+        };
+
+        let from = self.url()?;
+        let to = from.join(&location)?;
+        let mut headers = self.headers.clone();
+        if from.origin() != to.origin() {
+            headers.delete("authorization")?;
+            headers.delete("cookie")?;
+        }
+
+        let mut init = EdgeRequestInit::new();
+        init.redirect(worker_sys::RequestRedirect::Manual);
+        let keeps_method =
+            matches!(status, 307 | 308) || matches!(self.method, Method::Get | Method::Head);
+        let req = if keeps_method {
+            init.headers(headers.as_ref());
+            // a Request is a valid RequestInit for another request, which takes over its body
+            let req =
+                EdgeRequest::new_with_str_and_init(to.as_str(), self.edge_request.unchecked_ref())?;
+            EdgeRequest::new_with_request_and_init(&req, &init)?
+        } else {
+            headers.delete("content-type")?;
+            headers.delete("content-length")?;
+            init.method(&Method::Get.to_string());
+            init.headers(headers.as_ref());
+            EdgeRequest::new_with_str_and_init(to.as_str(), &init)?
+        };
+
+        let mut req: Request = req.into();
+        req.immutable = false;
+        Ok(Some(req))
+    }
+
+    /// Copy the method, URL, headers and Cloudflare properties of this `Request`, without its body.
+    pub(crate) fn without_body(&self) -> Result<Self> {
+        let mut init = EdgeRequestInit::new();
+        init.method(&self.method.to_string());
+        init.headers(self.headers.as_ref());
+
+        let mut req: Request =
+            EdgeRequest::new_with_str_and_init(&self.edge_request.url(), &init)?.into();
+        req.cf = self.cf.clone();
+        Ok(req)
+    }
+
+    /// Copy this `Request`, setting its redirect mode to `RequestRedirect::Manual`.
+    pub(crate) fn with_manual_redirect(self) -> Result<Self> {
+        let mut init = EdgeRequestInit::new();
+        init.redirect(worker_sys::RequestRedirect::Manual);
+        self.with_init(&init)
+    }
+
+    /// Check that this request's `Content-Type` is JSON, `application/json` or a `+json` media
+    /// type, before reading the body with `json`. Otherwise, the error returned is answered by
+    /// `Router::run` with `415 Unsupported Media Type`, so a client sending the wrong type is told
+    /// so, rather than that its body is malformed.
+    /// ```ignore
+    /// req.expect_json()?;
+    /// let order: Order = req.json().await?;
+    /// ```
+    pub fn expect_json(&self) -> Result<()> {
+        let media_type = media_type(self.headers.get("content-type")?.as_deref());
+        if media_type == "application/json" || media_type.ends_with("+json") {
+            Ok(())
+        } else {
+            Err(Error::UnsupportedMediaType)
+        }
+    }
+
+    /// Check that this request's `Content-Type` is a form, `application/x-www-form-urlencoded` as
+    /// read by `form`, or `multipart/form-data` as also read by `form_data`, answering it with
+    /// `415 Unsupported Media Type` otherwise like `expect_json`.
+    pub fn expect_form(&self) -> Result<()> {
+        match media_type(self.headers.get("content-type")?.as_deref()).as_str() {
+            "application/x-www-form-urlencoded" | "multipart/form-data" => Ok(()),
+            _ => Err(Error::UnsupportedMediaType),
+        }
+    }
+
     /// Access this request's body encoded as JSON.
     pub async fn json<B: DeserializeOwned>(&mut self) -> Result<B> {
-        if !self.body_used {
-            self.body_used = true;
-            return JsFuture::from(self.edge_request.json()?)
-                .await
-                .map_err(|e| {
-                    Error::JsError(
-                        e.as_string()
-                            .unwrap_or_else(|| "failed to get JSON for body value".into()),
-                    )
-                })
-                .and_then(|val| val.into_serde().map_err(Error::from));
+        let bytes = self.bytes().await?;
+        serde_json::from_slice(&bytes).map_err(Error::from)
+    }
+
+    /// Access this request's body encoded as JSON, for large payloads. The body is read as a
+    /// stream, appending each chunk to a single buffer as it arrives, and `B` is deserialized
+    /// straight from those bytes, without the intermediate JavaScript object or `String` of
+    /// `json`. The body limit set with `set_body_limit` is enforced as the body is read.
+    ///
+    /// `serde_json` can only deserialize incrementally from a blocking reader, so the body is
+    /// still held in memory once, in full, while it is parsed.
+    pub async fn json_streaming<B: DeserializeOwned>(&mut self) -> Result<B> {
+        let bytes = self.read_body(true).await?;
+        serde_json::from_slice(&bytes).map_err(Error::from)
+    }
+
+    /// Access this request's body encoded as JSON, rejecting fields which are not part of `B` and
+    /// nesting deeper than 32 levels. See `json_with_options`.
+    pub async fn json_strict<B: DeserializeOwned>(&mut self) -> Result<B> {
+        self.json_with_options(&JsonOptions::strict()).await
+    }
+
+    /// Access this request's body encoded as JSON, checked against `options`. A body which is not
+    /// valid JSON, breaks one of the options, or does not match `B` returns `Error::Json` with a
+    /// description of the problem and a `400` status, which a `Router` sends as a
+    /// `400 Bad Request` response.
+    pub async fn json_with_options<B: DeserializeOwned>(
+        &mut self,
+        options: &JsonOptions,
+    ) -> Result<B> {
+        let bytes = self.bytes().await?;
+        parse_json(&bytes, options)
+    }
+
+    /// Access this request's body as a URL-encoded form, deserialized into `T`. Unless the
+    /// `Content-Type` is `application/x-www-form-urlencoded`, returns
+    /// `Error::UnsupportedMediaType`, which a `Router` sends as a `415 Unsupported Media Type`
+    /// response.
+    /// Repeated keys, including array-style keys such as `tag[]`, deserialize into a sequence
+    /// field, e.g. a `Vec<String>`. A body which does not match `T` returns `Error::Json` with a
+    /// description of the problem and a `400` status, like `json_with_options`.
+    pub async fn form<T: DeserializeOwned>(&mut self) -> Result<T> {
+        let content_type = self.headers.get("content-type")?;
+        if media_type(content_type.as_deref()) != "application/x-www-form-urlencoded" {
+            return Err(Error::UnsupportedMediaType);
         }
 
-        Err(Error::BodyUsed)
+        let bytes = self.bytes().await?;
+        parse_form(&bytes)
     }
 
-    /// Access this request's body as plaintext.
+    /// Access this request's body as plaintext. Returns `Error::InvalidUtf8` if the body is not
+    /// valid UTF-8, use `text_lossy` to replace invalid sequences instead.
     pub async fn text(&mut self) -> Result<String> {
-        if !self.body_used {
-            self.body_used = true;
-            return JsFuture::from(self.edge_request.text()?)
-                .await
-                .map(|val| val.as_string().unwrap())
-                .map_err(|e| {
-                    Error::JsError(
-                        e.as_string()
-                            .unwrap_or_else(|| "failed to get text for body value".into()),
-                    )
-                });
-        }
+        let bytes = self.bytes().await?;
+        String::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)
+    }
 
-        Err(Error::BodyUsed)
+    /// Access this request's body as plaintext, replacing any invalid UTF-8 sequences with
+    /// `U+FFFD REPLACEMENT CHARACTER`.
+    pub async fn text_lossy(&mut self) -> Result<String> {
+        let bytes = self.bytes().await?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
     }
 
-    /// Access this request's body as raw bytes.
+    /// Access this request's body as raw bytes. Returns `Error::ContentLengthMismatch` if the
+    /// request declared a `Content-Length` which the body does not match, e.g. for a truncated
+    /// upload, or `Error::BodyTooLarge` if the body exceeds the limit set by `set_body_limit`.
     pub async fn bytes(&mut self) -> Result<Vec<u8>> {
-        if !self.body_used {
-            self.body_used = true;
-            return JsFuture::from(self.edge_request.array_buffer()?)
+        self.read_body(false).await
+    }
+
+    /// Access this request's body as a form-encoded payload and pull out fields and files.
+    pub async fn form_data(&mut self) -> Result<FormData> {
+        // parse the checked body by handing it back to the runtime in a `Response`
+        let mut bytes = self.bytes().await?;
+        let mut headers = Headers::new();
+        if let Some(content_type) = self.headers.get("content-type")? {
+            headers.set("content-type", &content_type)?;
+        }
+        let body = EdgeResponse::new_with_opt_u8_array_and_init(
+            Some(&mut bytes),
+            &ResponseInit {
+                status: 200,
+                headers,
+            }
+            .into(),
+        )?;
+        JsFuture::from(body.form_data()?)
+            .await
+            .map(|val| val.into())
+            .map_err(|e| {
+                Error::JsError(
+                    e.as_string()
+                        .unwrap_or_else(|| "failed to get form data from request".into()),
+                )
+            })
+    }
+
+    /// Limit the size of this request's body to `limit` bytes, or remove an existing limit with
+    /// `None`. The body is counted as it is streamed in, so the limit holds even when the
+    /// `Content-Length` header is missing or wrong: once more than `limit` bytes arrive, reading
+    /// stops and `Error::BodyTooLarge` is returned. `Router::body_limit` sets this for each
+    /// request it handles.
+    pub fn set_body_limit(&mut self, limit: Option<u64>) {
+        self.body_limit = limit;
+    }
+
+    /// Read the whole body, which every body accessor goes through, checking it against the body
+    /// limit and the declared `Content-Length`. The body is streamed in when it is limited or
+    /// `streamed` is set, and read as a single `ArrayBuffer` otherwise.
+    async fn read_body(&mut self, streamed: bool) -> Result<Vec<u8>> {
+        if self.body_used {
+            return Err(Error::BodyUsed);
+        }
+        self.body_used = true;
+
+        let bytes = match self.body_limit {
+            Some(limit) => self.read_limited(limit).await?,
+            None if streamed => self.read_limited(u64::MAX).await?,
+            None => JsFuture::from(self.edge_request.array_buffer()?)
                 .await
                 .map(|val| js_sys::Uint8Array::new(&val).to_vec())
                 .map_err(|e| {
@@ -121,28 +404,36 @@ impl Request {
                         e.as_string()
                             .unwrap_or_else(|| "failed to read array buffer from request".into()),
                     )
-                });
+                })?,
+        };
+
+        if let Some(expected) = self.content_length() {
+            let actual = bytes.len() as u64;
+            if actual != expected {
+                return Err(Error::ContentLengthMismatch { expected, actual });
+            }
         }
 
-        Err(Error::BodyUsed)
+        Ok(bytes)
     }
 
-    /// Access this request's body as a form-encoded payload and pull out fields and files.
-    pub async fn form_data(&mut self) -> Result<FormData> {
-        if !self.body_used {
-            self.body_used = true;
-            return JsFuture::from(self.edge_request.form_data()?)
-                .await
-                .map(|val| val.into())
-                .map_err(|e| {
-                    Error::JsError(
-                        e.as_string()
-                            .unwrap_or_else(|| "failed to get form data from request".into()),
-                    )
-                });
+    async fn read_limited(&mut self, limit: u64) -> Result<Vec<u8>> {
+        let body = match self.edge_request.body() {
+            Some(body) => body,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut bytes = Vec::new();
+        let mut chunks = ReadableStream::from_raw(body.unchecked_into()).into_stream();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = Uint8Array::new(&chunk?);
+            if bytes.len() as u64 + chunk.length() as u64 > limit {
+                return Err(Error::BodyTooLarge { limit });
+            }
+            bytes.extend(chunk.to_vec());
         }
 
-        Err(Error::BodyUsed)
+        Ok(bytes)
     }
 
     /// Get the `Headers` for this reuqest.
@@ -161,6 +452,72 @@ impl Request {
         Ok(&mut self.headers)
     }
 
+    /// Get the trailer headers sent after this request's body, which are only known once the body
+    /// has been read, e.g. when proxying gRPC-web. Returns `None` if the runtime does not expose
+    /// trailers, as the Workers runtime currently does not.
+    pub async fn trailers(&self) -> Result<Option<Headers>> {
+        let trailers = self.edge_request.trailers();
+        if trailers.is_undefined() || trailers.is_null() {
+            return Ok(None);
+        }
+
+        // the Fetch standard once defined trailers as a promise resolving once the body is read
+        let trailers = match trailers.dyn_into::<js_sys::Promise>() {
+            Ok(promise) => JsFuture::from(promise).await?,
+            Err(trailers) => trailers,
+        };
+        Ok(trailers.dyn_into::<worker_sys::Headers>().ok().map(Headers))
+    }
+
+    /// The length of the body declared by this request's `Content-Length` header, if it has a
+    /// valid one.
+    pub fn content_length(&self) -> Option<u64> {
+        self.headers
+            .get("content-length")
+            .ok()
+            .flatten()
+            .and_then(|len| len.trim().parse().ok())
+    }
+
+    /// The page which linked to this request, from its `Referer` header.
+    pub fn referrer(&self) -> Option<String> {
+        self.headers.get("referer").ok().flatten()
+    }
+
+    /// Whether the client asked for JSON in its `Accept` header, listing `application/json` or a
+    /// `+json` media type. Pair it with `Response::negotiated_json` and
+    /// `Response::negotiated_html`, which declare that the response varies by `Accept`.
+    /// ```ignore
+    /// if req.prefers_json() {
+    ///     Response::negotiated_json(&user)
+    /// } else {
+    ///     Response::negotiated_html(render_profile(&user))
+    /// }
+    /// ```
+    pub fn prefers_json(&self) -> bool {
+        crate::router::prefers_json(self.headers.get("accept").ok().flatten().as_deref())
+    }
+
+    /// The client software making this request, from its `User-Agent` header.
+    pub fn user_agent(&self) -> Option<String> {
+        self.headers.get("user-agent").ok().flatten()
+    }
+
+    /// The origin which initiated this request, from its `Origin` header. Browsers send it with
+    /// cross-origin requests and with any request other than `GET` or `HEAD`.
+    pub fn origin(&self) -> Option<String> {
+        self.headers.get("origin").ok().flatten()
+    }
+
+    /// The host this request was sent to, from its `Host` header, or otherwise its URL.
+    pub fn host(&self) -> Option<String> {
+        self.headers
+            .get("host")
+            .ok()
+            .flatten()
+            .or_else(|| self.url().ok()?.host_str().map(String::from))
+    }
+
     /// Access this request's Cloudflare-specific properties.
     pub fn cf(&self) -> &Cf {
         &self.cf
@@ -171,11 +528,110 @@ impl Request {
         self.method.clone()
     }
 
+    /// The HTTP Method this `Request` asks to be treated as, taken from the
+    /// `X-HTTP-Method-Override` header or a `_method` field in a form-encoded body. Only POST
+    /// requests may be overridden, and only to PUT, PATCH, or DELETE; in every other case this
+    /// returns `None`. Only URL-encoded forms are read, from a clone, leaving this request's body
+    /// unused, and a form which cannot be read is treated as having no override.
+    pub async fn method_override(&self) -> Result<Option<Method>> {
+        if self.method != Method::Post {
+            return Ok(None);
+        }
+
+        if let Some(method) = self.headers.get("x-http-method-override")? {
+            return Ok(overridable_method(&method));
+        }
+
+        let content_type = self.headers.get("content-type")?;
+        if media_type(content_type.as_deref()) != "application/x-www-form-urlencoded" {
+            return Ok(None);
+        }
+
+        Ok(self
+            .form_field("_method")
+            .await
+            .ok()
+            .flatten()
+            .and_then(|method| overridable_method(&method)))
+    }
+
+    /// Read a field of a form-encoded body from a copy of this request, leaving its body unread
+    /// for the handler. Returns `None` if the body isn't a form or has no such field. Multipart
+    /// bodies, which may hold file uploads, are only read when their declared `Content-Length` is
+    /// at most `FORM_FIELD_MULTIPART_LIMIT`.
+    pub(crate) async fn form_field(&self, name: &str) -> Result<Option<String>> {
+        let content_type = self.headers.get("content-type")?;
+        match media_type(content_type.as_deref()).as_str() {
+            "application/x-www-form-urlencoded" => {
+                let mut form = self.clone()?;
+                form.set_body_limit(self.body_limit);
+                let body = form.bytes().await?;
+                Ok(url::form_urlencoded::parse(&body)
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.into_owned()))
+            }
+            "multipart/form-data" => {
+                if !matches!(self.content_length(), Some(len) if len <= FORM_FIELD_MULTIPART_LIMIT)
+                {
+                    return Ok(None);
+                }
+                let mut form = self.clone()?;
+                form.set_body_limit(Some(FORM_FIELD_MULTIPART_LIMIT));
+                match form.form_data().await?.get(name) {
+                    Some(FormEntry::Field(value)) => Ok(Some(value)),
+                    _ => Ok(None),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The redirect mode of this `Request`. Note that incoming requests always have the `manual`
+    /// redirect mode.
+    pub fn redirect(&self) -> RequestRedirect {
+        self.edge_request.redirect().into()
+    }
+
+    /// The mode of this `Request`, or `None` if the runtime does not provide one.
+    pub fn mode(&self) -> Option<RequestMode> {
+        self.edge_request
+            .mode()
+            .and_then(|mode| RequestMode::from_str(&mode))
+    }
+
+    /// The credentials mode of this `Request`, or `None` if the runtime does not provide one.
+    pub fn credentials(&self) -> Option<RequestCredentials> {
+        self.edge_request
+            .credentials()
+            .and_then(|credentials| RequestCredentials::from_str(&credentials))
+    }
+
     /// The URL Path of this `Request`.
     pub fn path(&self) -> String {
         self.path.clone()
     }
 
+    /// The scheme the client used to send this `Request`, e.g. `https`. Requests reach a Worker
+    /// through Cloudflare's edge, so the scheme is taken from the first of these that is present:
+    /// the `CF-Visitor` header set by Cloudflare, the `X-Forwarded-Proto` header, and finally the
+    /// scheme of the request URL.
+    pub fn scheme(&self) -> String {
+        let cf_visitor = self.headers.get("cf-visitor").ok().flatten();
+        let forwarded_proto = self.headers.get("x-forwarded-proto").ok().flatten();
+        let url = self.url().ok();
+
+        detect_scheme(
+            cf_visitor.as_deref(),
+            forwarded_proto.as_deref(),
+            url.as_ref().map(|url| url.scheme()),
+        )
+    }
+
+    /// Whether the client sent this `Request` over HTTPS, see `scheme` for how this is detected.
+    pub fn is_secure(&self) -> bool {
+        self.scheme() == "https"
+    }
+
     /// The parsed [`url::Url`] of this `Request`.
     pub fn url(&self) -> Result<Url> {
         let url = self.edge_request.url();
@@ -195,3 +651,158 @@ impl Request {
         &self.edge_request
     }
 }
+
+fn overridable_method(method: &str) -> Option<Method> {
+    match method.trim().to_ascii_uppercase().as_str() {
+        "PUT" => Some(Method::Put),
+        "PATCH" => Some(Method::Patch),
+        "DELETE" => Some(Method::Delete),
+        _ => None,
+    }
+}
+
+fn detect_scheme(
+    cf_visitor: Option<&str>,
+    forwarded_proto: Option<&str>,
+    url_scheme: Option<&str>,
+) -> String {
+    let visitor_scheme = cf_visitor
+        .and_then(|visitor| serde_json::from_str::<serde_json::Value>(visitor).ok())
+        .and_then(|visitor| visitor.get("scheme")?.as_str().map(str::to_string));
+    let forwarded_scheme = forwarded_proto
+        .and_then(|proto| proto.split(',').next())
+        .map(|proto| proto.trim().to_string())
+        .filter(|proto| !proto.is_empty());
+
+    visitor_scheme
+        .or(forwarded_scheme)
+        .or_else(|| url_scheme.map(str::to_string))
+        .unwrap_or_else(|| "http".into())
+        .to_ascii_lowercase()
+}
+
+fn parse_form<T: DeserializeOwned>(body: &[u8]) -> Result<T> {
+    // normalize array-style `key[]` fields into repeated `key` fields
+    let normalized = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(url::form_urlencoded::parse(body).map(|(key, value)| {
+            let key = match key.strip_suffix("[]") {
+                Some(key) => key.to_string(),
+                None => key.into_owned(),
+            };
+            (key, value)
+        }))
+        .finish();
+
+    serde_html_form::from_str(&normalized)
+        .map_err(|e| Error::Json((format!("invalid form body: {}", e), 400)))
+}
+
+fn parse_json<B: DeserializeOwned>(body: &[u8], options: &JsonOptions) -> Result<B> {
+    let bad_request = |msg: String| Error::Json((msg, 400));
+
+    let input: Value = serde_json::from_slice(body)
+        .map_err(|e| bad_request(format!("invalid JSON body: {}", e)))?;
+    if let Some(max_depth) = options.max_depth {
+        if json_depth(&input) > max_depth {
+            return Err(bad_request(format!(
+                "JSON body is nested deeper than {} levels",
+                max_depth
+            )));
+        }
+    }
+
+    if !options.deny_unknown_fields {
+        return B::deserialize(input).map_err(|e| bad_request(format!("invalid JSON body: {}", e)));
+    }
+
+    let mut unknown = None;
+    let decoded: B = serde_ignored::deserialize(input, |path| {
+        if unknown.is_none() {
+            unknown = Some(path.to_string());
+        }
+    })
+    .map_err(|e| bad_request(format!("invalid JSON body: {}", e)))?;
+    match unknown {
+        Some(field) => Err(bad_request(format!(
+            "JSON body has an unknown field `{}`",
+            field
+        ))),
+        None => Ok(decoded),
+    }
+}
+
+fn json_depth(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => 1 + items.iter().map(json_depth).max().unwrap_or(0),
+        Value::Object(fields) => 1 + fields.values().map(json_depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+#[test]
+fn form_fields_deserialize_with_repeated_keys() {
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Form {
+        name: String,
+        age: u8,
+        tag: Vec<String>,
+        note: Option<String>,
+    }
+
+    assert_eq!(
+        parse_form::<Form>(b"name=J%C3%BCrgen+B&age=30&tag=a&tag[]=b").unwrap(),
+        Form {
+            name: "Jürgen B".into(),
+            age: 30,
+            tag: vec!["a".into(), "b".into()],
+            note: None,
+        }
+    );
+    assert!(matches!(
+        parse_form::<Form>(b"name=x&age=old"),
+        Err(Error::Json((_, 400)))
+    ));
+}
+
+#[test]
+fn scheme_detection_prefers_cloudflare_headers() {
+    assert_eq!(
+        detect_scheme(Some(r#"{"scheme":"https"}"#), Some("http"), Some("http")),
+        "https"
+    );
+    assert_eq!(
+        detect_scheme(None, Some("HTTPS, http"), Some("http")),
+        "https"
+    );
+    assert_eq!(detect_scheme(Some("not json"), None, Some("http")), "http");
+    assert_eq!(detect_scheme(None, None, None), "http");
+}
+
+#[test]
+fn strict_json_rejects_unknown_fields_and_deep_nesting() {
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Item {
+        #[serde(alias = "title")]
+        name: String,
+    }
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Order {
+        items: Vec<Item>,
+    }
+
+    let strict = JsonOptions::strict();
+    let order: Order = parse_json(br#"{"items":[{"name":"a"}]}"#, &strict).unwrap();
+    assert_eq!(order.items[0].name, "a");
+    assert!(parse_json::<Order>(br#"{"items":[{"title":"a"}]}"#, &strict).is_ok());
+
+    let err = parse_json::<Order>(br#"{"items":[{"name":"a","qty":2}]}"#, &strict).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "JSON body has an unknown field `items.0.qty` (status: 400)"
+    );
+    assert!(parse_json::<Order>(br#"{"items":[],"extra":1}"#, &JsonOptions::default()).is_ok());
+
+    let deep = format!("{}{}", "[".repeat(33), "]".repeat(33));
+    assert!(parse_json::<Value>(deep.as_bytes(), &strict).is_err());
+    assert_eq!(json_depth(&serde_json::json!({"a": [1, {"b": 2}]})), 3);
+}
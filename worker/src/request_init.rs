@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 
+use crate::abort::AbortSignal;
 use crate::headers::Headers;
 use crate::http::Method;
 
@@ -21,6 +22,15 @@ pub struct RequestInit {
     /// follow. Note, however, that the incoming Request property of a FetchEvent will have redirect
     /// mode manual.
     pub redirect: RequestRedirect,
+    /// The mode to use for the `Request`, e.g. `cors` or `same-origin`. Left unset by default, so
+    /// the runtime's default applies. See `RequestInit::with_mode`.
+    pub mode: Option<RequestMode>,
+    /// Whether credentials such as cookies are sent with the `Request`. Left unset by default, so
+    /// the runtime's default applies. See `RequestInit::with_credentials`.
+    pub credentials: Option<RequestCredentials>,
+    /// An `AbortSignal` used to cancel the outbound `Request` once its associated
+    /// `AbortController` is aborted.
+    pub signal: Option<AbortSignal>,
 }
 
 impl RequestInit {
@@ -43,6 +53,26 @@ impl RequestInit {
         self
     }
 
+    /// Set the mode of the `Request`.
+    ///
+    /// **Note:** the Workers runtime does not implement `mode` for outbound requests, and throws
+    /// "not implemented" when a `Request` is constructed or fetched with one set. This is only
+    /// useful in other runtimes, or for a `Request` which is never sent.
+    pub fn with_mode(&mut self, mode: Option<RequestMode>) -> &mut Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set whether credentials are sent with the `Request`.
+    ///
+    /// **Note:** the Workers runtime does not implement `credentials` for outbound requests, and
+    /// throws "not implemented" when a `Request` is constructed or fetched with it set. This is
+    /// only useful in other runtimes, or for a `Request` which is never sent.
+    pub fn with_credentials(&mut self, credentials: Option<RequestCredentials>) -> &mut Self {
+        self.credentials = credentials;
+        self
+    }
+
     pub fn with_body(&mut self, body: Option<JsValue>) -> &mut Self {
         self.body = body;
         self
@@ -52,6 +82,11 @@ impl RequestInit {
         self.cf = props;
         self
     }
+
+    pub fn with_signal(&mut self, signal: Option<&AbortSignal>) -> &mut Self {
+        self.signal = signal.cloned();
+        self
+    }
 }
 
 impl From<&RequestInit> for worker_sys::RequestInit {
@@ -59,8 +94,15 @@ impl From<&RequestInit> for worker_sys::RequestInit {
         let mut inner = worker_sys::RequestInit::new();
         inner.headers(req.headers.as_ref());
         inner.method(&req.method.to_string());
-        inner.redirect(req.redirect.clone().into());
+        inner.redirect(req.redirect.into());
+        if let Some(mode) = req.mode {
+            inner.mode(mode.into());
+        }
+        if let Some(credentials) = req.credentials {
+            inner.credentials(credentials.into());
+        }
         inner.body(req.body.as_ref());
+        inner.signal(req.signal.as_ref().map(|signal| signal.inner()));
 
         // set the Cloudflare-specific `cf` property on FFI RequestInit
         #[allow(unused_unsafe)]
@@ -89,6 +131,9 @@ impl Default for RequestInit {
             cf: CfProperties::default(),
             method: Method::Get,
             redirect: RequestRedirect::default(),
+            mode: None,
+            credentials: None,
+            signal: None,
         }
     }
 }
@@ -240,7 +285,7 @@ impl From<&CfProperties> for JsValue {
     }
 }
 
-fn set_prop(target: &Object, key: &JsValue, val: &JsValue) {
+pub(crate) fn set_prop(target: &Object, key: &JsValue, val: &JsValue) {
     #[allow(unused_unsafe)]
     let r = unsafe { ::js_sys::Reflect::set(&target, key, val) };
     debug_assert!(
@@ -342,3 +387,75 @@ impl From<RequestRedirect> for worker_sys::RequestRedirect {
         }
     }
 }
+
+impl From<worker_sys::RequestRedirect> for RequestRedirect {
+    fn from(redir: worker_sys::RequestRedirect) -> Self {
+        match redir {
+            worker_sys::RequestRedirect::Error => RequestRedirect::Error,
+            worker_sys::RequestRedirect::Manual => RequestRedirect::Manual,
+            _ => RequestRedirect::Follow,
+        }
+    }
+}
+
+/// The [mode](https://developer.mozilla.org/en-US/docs/Web/API/Request/mode) of a `Request`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestMode {
+    SameOrigin,
+    NoCors,
+    Cors,
+    Navigate,
+}
+
+impl From<RequestMode> for &str {
+    fn from(mode: RequestMode) -> Self {
+        match mode {
+            RequestMode::SameOrigin => "same-origin",
+            RequestMode::NoCors => "no-cors",
+            RequestMode::Cors => "cors",
+            RequestMode::Navigate => "navigate",
+        }
+    }
+}
+
+impl RequestMode {
+    pub(crate) fn from_str(mode: &str) -> Option<Self> {
+        match mode {
+            "same-origin" => Some(RequestMode::SameOrigin),
+            "no-cors" => Some(RequestMode::NoCors),
+            "cors" => Some(RequestMode::Cors),
+            "navigate" => Some(RequestMode::Navigate),
+            _ => None,
+        }
+    }
+}
+
+/// The [credentials](https://developer.mozilla.org/en-US/docs/Web/API/Request/credentials) mode
+/// of a `Request`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestCredentials {
+    Omit,
+    SameOrigin,
+    Include,
+}
+
+impl From<RequestCredentials> for &str {
+    fn from(credentials: RequestCredentials) -> Self {
+        match credentials {
+            RequestCredentials::Omit => "omit",
+            RequestCredentials::SameOrigin => "same-origin",
+            RequestCredentials::Include => "include",
+        }
+    }
+}
+
+impl RequestCredentials {
+    pub(crate) fn from_str(credentials: &str) -> Option<Self> {
+        match credentials {
+            "omit" => Some(RequestCredentials::Omit),
+            "same-origin" => Some(RequestCredentials::SameOrigin),
+            "include" => Some(RequestCredentials::Include),
+            _ => None,
+        }
+    }
+}
@@ -1,10 +1,28 @@
+use crate::cache_control::CacheControl;
+use crate::date::Date;
 use crate::error::Error;
 use crate::headers::Headers;
+use crate::http::Method;
+use crate::metrics::Metrics;
+use crate::mime::{is_compressible, negotiate_encoding, ContentEncoding, ContentType};
+use crate::request::Request;
+use crate::sse::{into_sse_stream, SseEvent};
+use crate::transform_stream::TransformStream;
 use crate::Result;
 
+use std::time::Duration;
+
+use futures::{future, stream, Stream, StreamExt, TryStream, TryStreamExt};
+use js_sys::Uint8Array;
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
-use worker_sys::{Response as EdgeResponse, ResponseInit as EdgeResponseInit};
+use wasm_streams::{ReadableStream, WritableStream};
+use worker_sys::{
+    console_error, global::random_uuid, FixedLengthStream as EdgeFixedLengthStream,
+    Response as EdgeResponse, ResponseInit as EdgeResponseInit,
+};
 
 #[derive(Debug)]
 pub enum ResponseBody {
@@ -15,6 +33,16 @@ pub enum ResponseBody {
 
 const CONTENT_TYPE: &str = "content-type";
 
+/// Options for shaping a JSON response body with `Response::from_json_with`. The default options
+/// leave the output of `serde` unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonResponseOptions {
+    /// Omit object fields whose value is `null`, such as `None` fields.
+    pub skip_nulls: bool,
+    /// Rename object keys from `snake_case` to `camelCase`.
+    pub camel_case: bool,
+}
+
 /// A [Response](https://developer.mozilla.org/en-US/docs/Web/API/Response) representation for
 /// working with or returning a response to a `Request`.
 #[derive(Debug)]
@@ -22,24 +50,66 @@ pub struct Response {
     body: ResponseBody,
     headers: Headers,
     status_code: u16,
+    status_text: Option<String>,
 }
 
 impl Response {
     /// Create a `Response` using `B` as the body encoded as JSON. Sets the associated
     /// `Content-Type` header for the `Response` as `application/json`.
+    ///
+    /// Returns `Error::Serialization` if `value` cannot be encoded, e.g. a map with non-string keys.
     pub fn from_json<B: Serialize>(value: &B) -> Result<Self> {
-        if let Ok(data) = serde_json::to_string(value) {
-            let mut headers = Headers::new();
-            headers.set(CONTENT_TYPE, "application/json")?;
+        let data = serde_json::to_vec(value).map_err(serialization_error)?;
+        let mut headers = Headers::new();
+        headers.set(CONTENT_TYPE, "application/json")?;
 
-            return Ok(Self {
-                body: ResponseBody::Body(data.into_bytes()),
-                headers,
-                status_code: 200,
-            });
-        }
+        Ok(Self {
+            body: ResponseBody::Body(data),
+            headers,
+            status_code: 200,
+            status_text: None,
+        })
+    }
+
+    /// Create a `Response` like `from_json`, shaping the encoded body with `options`, e.g. to omit
+    /// `null` fields without a separate type annotated for serialization.
+    /// ```ignore
+    /// let options = JsonResponseOptions {
+    ///     skip_nulls: true,
+    ///     camel_case: true,
+    /// };
+    /// Response::from_json_with(&user, &options)
+    /// ```
+    pub fn from_json_with<B: Serialize>(value: &B, options: &JsonResponseOptions) -> Result<Self> {
+        let value = serde_json::to_value(value).map_err(serialization_error)?;
+        Self::from_json(&shape_json(value, options))
+    }
+
+    /// Create a `Response` like `from_json`, for a handler which chooses between JSON and another
+    /// representation from the `Accept` header, e.g. with `Request::prefers_json`. Appends
+    /// `Accept` to the `Vary` header, so caches don't serve one representation in place of another.
+    pub fn negotiated_json<B: Serialize>(value: &B) -> Result<Self> {
+        Self::from_json(value)?.append_vary("Accept")
+    }
 
-        Err(Error::Json(("Failed to encode data to json".into(), 500)))
+    /// Create a `Response` like `from_html`, appending `Accept` to the `Vary` header. See
+    /// `negotiated_json`.
+    pub fn negotiated_html(html: impl AsRef<str>) -> Result<Self> {
+        Self::from_html(html)?.append_vary("Accept")
+    }
+
+    /// Create a `Response` rendering every metric in `metrics` in the Prometheus text exposition
+    /// format, with the matching `Content-Type`.
+    pub fn from_metrics(metrics: &Metrics) -> Result<Self> {
+        let mut headers = Headers::new();
+        headers.set(CONTENT_TYPE, "text/plain; version=0.0.4")?;
+
+        Ok(Self {
+            body: ResponseBody::Body(metrics.render().into_bytes()),
+            headers,
+            status_code: 200,
+            status_text: None,
+        })
     }
 
     /// Create a `Response` using the body encoded as HTML. Sets the associated `Content-Type`
@@ -53,6 +123,7 @@ impl Response {
             body: ResponseBody::Body(data),
             headers,
             status_code: 200,
+            status_text: None,
         })
     }
 
@@ -66,9 +137,187 @@ impl Response {
             body: ResponseBody::Body(bytes),
             headers,
             status_code: 200,
+            status_text: None,
         })
     }
 
+    /// Create a `Response` serving the parts of `bytes` asked for by `range`, the value of the
+    /// request's `Range` header, with `content_type` as the `Content-Type` of the content.
+    ///
+    /// A single satisfiable range is answered with `206 Partial Content` and a `Content-Range`
+    /// header, and several with a `206` `multipart/byteranges` body holding each range as a part
+    /// with its own `Content-Range`. If none of the ranges can be satisfied, the response is
+    /// `416 Range Not Satisfiable`. Without a `Range` header, or with one this does not
+    /// understand, all of `bytes` are served with `200 OK`. `If-Range` is not checked.
+    /// ```ignore
+    /// let range = req.headers().get("range")?;
+    /// Response::from_bytes_with_range(pdf, "application/pdf", range.as_deref())
+    /// ```
+    pub fn from_bytes_with_range(
+        bytes: Vec<u8>,
+        content_type: &str,
+        range: Option<&str>,
+    ) -> Result<Self> {
+        let len = bytes.len() as u64;
+        let mut headers = Headers::new();
+        headers.set("accept-ranges", "bytes")?;
+
+        let ranges = match range.and_then(|range| parse_ranges(range, len)) {
+            Some(ranges) => ranges,
+            None => {
+                headers.set(CONTENT_TYPE, content_type)?;
+                return Ok(Self::from_bytes(bytes)?.with_headers(headers));
+            }
+        };
+
+        let body = match ranges.as_slice() {
+            [] => {
+                headers.set("content-range", &format!("bytes */{}", len))?;
+                return Ok(Self::empty()?.with_status(416).with_headers(headers));
+            }
+            [(start, end)] => {
+                headers.set(CONTENT_TYPE, content_type)?;
+                headers.set("content-range", &content_range(*start, *end, len))?;
+                bytes[*start as usize..=*end as usize].to_vec()
+            }
+            ranges => {
+                let boundary = random_uuid().replace('-', "");
+                headers.set(
+                    CONTENT_TYPE,
+                    &format!("multipart/byteranges; boundary={}", boundary),
+                )?;
+                multipart_byteranges(&bytes, ranges, content_type, &boundary)
+            }
+        };
+
+        Ok(Self::from_bytes(body)?
+            .with_status(206)
+            .with_headers(headers))
+    }
+
+    /// Create a `Response` using a `Stream` for the body, sending each chunk to the client as it is
+    /// produced. If the stream yields an error, the response body is aborted.
+    ///
+    /// The body respects backpressure: the next chunk is only pulled from `stream` once the
+    /// previous one has been taken by the client, so a fast source feeding a slow client is not
+    /// buffered in memory.
+    pub fn from_stream<S>(stream: S) -> Result<Self>
+    where
+        S: TryStream + 'static,
+        S::Ok: Into<Vec<u8>>,
+        S::Error: Into<Error>,
+    {
+        let body = into_readable_stream(stream).into_raw().unchecked_into();
+        let response =
+            EdgeResponse::new_with_opt_stream_and_init(Some(&body), &EdgeResponseInit::new())?;
+
+        Ok(Self {
+            body: ResponseBody::Stream(response),
+            headers: Headers::new(),
+            status_code: 200,
+            status_text: None,
+        })
+    }
+
+    /// Create a `Response` using a `Stream` for the body, where the total length of the body is
+    /// known to be `len` bytes. Sets the associated `Content-Length` header, and uses the
+    /// runtime's `FixedLengthStream` when available, which errors the body if the stream produces
+    /// more or fewer bytes than declared. Otherwise the bytes are counted as they are streamed,
+    /// and the body errors with `Error::ContentLengthMismatch` once they cannot match. Like
+    /// `from_stream`, the body respects backpressure.
+    pub fn from_stream_with_length<S>(stream: S, len: u64) -> Result<Self>
+    where
+        S: TryStream + 'static,
+        S::Ok: Into<Vec<u8>>,
+        S::Error: Into<Error>,
+    {
+        let mut headers = Headers::new();
+        headers.set("content-length", &len.to_string())?;
+
+        let fixed = match EdgeFixedLengthStream::new(len as f64) {
+            Ok(fixed) => fixed,
+            Err(_) => {
+                return Ok(Self::from_stream(check_length(stream, len))?.with_headers(headers))
+            }
+        };
+
+        let mut source = into_readable_stream(stream);
+        let mut sink = WritableStream::from_raw(fixed.writable().unchecked_into());
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Err(e) = source.pipe_to(&mut sink).await {
+                console_error!(
+                    "streamed body did not match its content-length: {}",
+                    Error::from(e)
+                );
+            }
+        });
+
+        let response = EdgeResponse::new_with_opt_stream_and_init(
+            Some(&fixed.readable()),
+            &EdgeResponseInit::new(),
+        )?;
+
+        Ok(Self {
+            body: ResponseBody::Stream(response),
+            headers,
+            status_code: 200,
+            status_text: None,
+        })
+    }
+
+    /// Create a `Response` which streams `events` to the client as
+    /// [server-sent events](https://developer.mozilla.org/en-US/docs/Web/API/Server-sent_events).
+    /// Sets the associated `Content-Type` header as `text/event-stream`, and disables caching.
+    ///
+    /// With a `keepalive_interval`, a `: ping` comment is sent every interval between events, so
+    /// proxies do not close an idle connection. The timer stops when `events` ends or the client
+    /// disconnects.
+    /// ```ignore
+    /// let events = stream::iter(updates).map(|update| Ok::<_, Error>(SseEvent::new(update)));
+    /// Response::from_sse(events, Some(Duration::from_secs(15)))
+    /// ```
+    pub fn from_sse<S>(events: S, keepalive_interval: Option<Duration>) -> Result<Self>
+    where
+        S: TryStream<Ok = SseEvent> + 'static,
+        S::Error: Into<Error>,
+    {
+        let mut headers = Headers::new();
+        headers.set(CONTENT_TYPE, "text/event-stream")?;
+        headers.set("cache-control", "no-cache")?;
+
+        Ok(Self::from_stream(into_sse_stream(events, keepalive_interval))?.with_headers(headers))
+    }
+
+    /// Create a `Response` which streams the items of `items` to the client as a JSON array,
+    /// serializing each item as it arrives. Sets the associated `Content-Type` header for the
+    /// `Response` as `application/json`.
+    pub fn json_array_stream<S, T>(items: S) -> Result<Self>
+    where
+        S: TryStream<Ok = T> + 'static,
+        S::Error: Into<Error>,
+        T: Serialize,
+    {
+        let mut first = true;
+        let elements = items.map_err(Into::into).and_then(move |item| {
+            let separator: &[u8] = if first { b"" } else { b"," };
+            first = false;
+            future::ready(
+                serde_json::to_vec(&item)
+                    .map(|json| [separator, &json].concat())
+                    .map_err(Error::from),
+            )
+        });
+
+        let body = stream::once(future::ready(Ok(b"[".to_vec())))
+            .chain(elements)
+            .chain(stream::once(future::ready(Ok(b"]".to_vec()))));
+
+        let mut headers = Headers::new();
+        headers.set(CONTENT_TYPE, "application/json")?;
+
+        Ok(Self::from_stream(body)?.with_headers(headers))
+    }
+
     /// Create a `Response` using unprocessed text provided. Sets the associated `Content-Type`
     /// header for the `Response` as `text/plain`.
     pub fn ok(body: impl Into<String>) -> Result<Self> {
@@ -79,6 +328,7 @@ impl Response {
             body: ResponseBody::Body(body.into().into_bytes()),
             headers,
             status_code: 200,
+            status_text: None,
         })
     }
 
@@ -88,9 +338,85 @@ impl Response {
             body: ResponseBody::Empty,
             headers: Headers::new(),
             status_code: 200,
+            status_text: None,
+        })
+    }
+
+    /// Create a `301 Moved Permanently` redirect, setting the `Location` header to `url`.
+    pub fn moved_permanently(url: impl AsRef<str>) -> Result<Self> {
+        Self::redirect_with_status(url.as_ref(), 301)
+    }
+
+    /// Create a `302 Found` redirect, setting the `Location` header to `url`.
+    pub fn found(url: impl AsRef<str>) -> Result<Self> {
+        Self::redirect_with_status(url.as_ref(), 302)
+    }
+
+    /// Create a `303 See Other` redirect, setting the `Location` header to `url`. Clients follow
+    /// it with a GET request, making it the right choice after handling a form POST.
+    pub fn see_other(url: impl AsRef<str>) -> Result<Self> {
+        Self::redirect_with_status(url.as_ref(), 303)
+    }
+
+    /// Create a `307 Temporary Redirect`, setting the `Location` header to `url`. Unlike `found`,
+    /// clients must repeat the request with the same method and body.
+    pub fn temporary_redirect(url: impl AsRef<str>) -> Result<Self> {
+        Self::redirect_with_status(url.as_ref(), 307)
+    }
+
+    /// Create a `308 Permanent Redirect`, setting the `Location` header to `url`. Unlike
+    /// `moved_permanently`, clients must repeat the request with the same method and body.
+    pub fn permanent_redirect(url: impl AsRef<str>) -> Result<Self> {
+        Self::redirect_with_status(url.as_ref(), 308)
+    }
+
+    fn redirect_with_status(url: &str, status: u16) -> Result<Self> {
+        let mut headers = Headers::new();
+        headers.set("location", url)?;
+
+        Ok(Self {
+            body: ResponseBody::Empty,
+            headers,
+            status_code: status,
+            status_text: None,
         })
     }
 
+    /// Create a `304 Not Modified` response if the request's `If-Modified-Since` header shows the
+    /// client already has the version of a resource last modified at `last_modified`, otherwise
+    /// `None` so the full response can be sent. Only `GET` and `HEAD` requests are considered, and
+    /// dates are compared to the second, as HTTP dates are.
+    /// ```ignore
+    /// if let Some(res) = Response::not_modified_since(&req, &modified)? {
+    ///     return Ok(res);
+    /// }
+    /// Response::ok(body).map(|res| res.with_last_modified(modified))
+    /// ```
+    pub fn not_modified_since(req: &Request, last_modified: &Date) -> Result<Option<Self>> {
+        if !matches!(req.method(), Method::Get | Method::Head) {
+            return Ok(None);
+        }
+
+        let since = match req.headers().get("if-modified-since")? {
+            Some(since) => Date::parse(&since),
+            None => None,
+        };
+        match since {
+            Some(since) if is_unmodified(last_modified.as_millis(), since.as_millis()) => {
+                let mut headers = Headers::new();
+                headers.set("last-modified", &last_modified.to_utc_string())?;
+
+                Ok(Some(Self {
+                    body: ResponseBody::Empty,
+                    headers,
+                    status_code: 304,
+                    status_text: None,
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+
     /// A helper method to send an error message to a client. Will return `Err` if the status code
     /// provided is outside the valid HTTP error range of 400-599.
     pub fn error(msg: impl Into<String>, status: u16) -> Result<Self> {
@@ -104,26 +430,54 @@ impl Response {
             body: ResponseBody::Body(msg.into().into_bytes()),
             headers: Headers::new(),
             status_code: status,
+            status_text: None,
         })
     }
 
+    /// Like `Response::error`, but with a JSON body of the form
+    /// `{"error": "Not Found", "status": 404}` for API clients, and `Content-Type` set to
+    /// `application/json`.
+    pub fn error_json(message: &str, status: u16) -> Result<Self> {
+        if !(400..=599).contains(&status) {
+            return Err(Error::Internal(
+                "provided error status code is invalid".into(),
+            ));
+        }
+
+        let body = serde_json::json!({ "error": message, "status": status });
+        Ok(Self::from_json(&body)?.with_status(status))
+    }
+
     /// Get the HTTP Status code of this `Response`.
     pub fn status_code(&self) -> u16 {
         self.status_code
     }
 
-    /// Access this response's body as plaintext.
+    /// Whether this `Response` has a `2xx` success status code.
+    pub fn is_success(&self) -> bool {
+        (200..=299).contains(&self.status_code)
+    }
+
+    /// Whether this `Response` has a `3xx` redirection status code.
+    pub fn is_redirect(&self) -> bool {
+        (300..=399).contains(&self.status_code)
+    }
+
+    /// Whether this `Response` has a `4xx` client error status code.
+    pub fn is_client_error(&self) -> bool {
+        (400..=499).contains(&self.status_code)
+    }
+
+    /// Whether this `Response` has a `5xx` server error status code.
+    pub fn is_server_error(&self) -> bool {
+        (500..=599).contains(&self.status_code)
+    }
+
+    /// Access this response's body as plaintext. Returns `Error::InvalidUtf8` if the body is not
+    /// valid UTF-8. Like `bytes`, this leaves the body in place to be read again or sent.
     pub async fn text(&mut self) -> Result<String> {
-        match &self.body {
-            ResponseBody::Body(bytes) => {
-                Ok(String::from_utf8(bytes.clone()).map_err(|e| Error::from(e.to_string()))?)
-            }
-            ResponseBody::Empty => Ok(String::new()),
-            ResponseBody::Stream(response) => JsFuture::from(response.text()?)
-                .await
-                .map(|value| value.as_string().unwrap())
-                .map_err(Error::from),
-        }
+        let bytes = self.bytes().await?;
+        String::from_utf8(bytes).map_err(|_| Error::InvalidUtf8)
     }
 
     /// Access this response's body encoded as JSON.
@@ -135,15 +489,103 @@ impl Response {
         serde_json::from_str(&self.text().await?).map_err(Error::from)
     }
 
-    /// Access this response's body encoded as raw bytes.
+    /// Access this response's body encoded as raw bytes. A streamed body is read in full and kept,
+    /// so the body can be read again, and the `Response` can still be sent afterwards.
     pub async fn bytes(&mut self) -> Result<Vec<u8>> {
         match &self.body {
             ResponseBody::Body(bytes) => Ok(bytes.clone()),
             ResponseBody::Empty => Ok(Vec::new()),
-            ResponseBody::Stream(response) => JsFuture::from(response.text()?)
-                .await
-                .map(|value| value.as_string().unwrap().into_bytes())
-                .map_err(Error::from),
+            ResponseBody::Stream(response) => {
+                let bytes = JsFuture::from(response.array_buffer()?)
+                    .await
+                    .map(|value| Uint8Array::new(&value).to_vec())
+                    .map_err(Error::from)?;
+                self.body = ResponseBody::Body(bytes.clone());
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Stream this response's body through `transform`, returning a `Response` with the same
+    /// status and headers whose body is the transformed stream. The `Content-Length` header is
+    /// removed, since the transform may change the length of the body.
+    /// ```ignore
+    /// let redact = TransformStream::new(|chunk| Ok(redact_secrets(chunk)))?;
+    /// res.pipe_through(redact)
+    /// ```
+    pub fn pipe_through(self, transform: TransformStream) -> Result<Self> {
+        let mut headers = self.headers.clone();
+        let status = self.status_code;
+        let status_text = self.status_text.clone();
+        let response = EdgeResponse::from(self);
+        let body = match response.body() {
+            Some(body) => body,
+            None => return Ok(response.into()),
+        };
+
+        headers.delete("content-length")?;
+        let mut init = EdgeResponseInit::new();
+        init.status(status);
+        if let Some(status_text) = &status_text {
+            init.status_text(status_text);
+        }
+        init.headers(&headers.0);
+        // a TransformStream is a readable and writable pair
+        let piped = body.pipe_through(transform.inner().unchecked_ref());
+        Ok(EdgeResponse::new_with_opt_stream_and_init(Some(&piped), &init)?.into())
+    }
+
+    /// Compress this response's body with the best encoding accepted by `req`'s `Accept-Encoding`
+    /// header among Brotli, gzip and deflate, preferring them in that order when the client has
+    /// no preference. Only text-based bodies, such as HTML, JSON or SVG, are compressed: responses
+    /// of other types, e.g. images, archives and fonts which are compressed already, partial
+    /// `206` responses, responses with a body smaller than `min_size` bytes, which are not worth
+    /// compressing, and responses which already have a `Content-Encoding` are left unchanged.
+    ///
+    /// Brotli and gzip are applied by the runtime as the body is sent, according to the
+    /// `Content-Encoding` header, while deflate is applied with a `CompressionStream`. Compressed
+    /// responses vary by `Accept-Encoding`.
+    /// ```ignore
+    /// Response::from_html(page)?.compress(&req, 1024)
+    /// ```
+    pub fn compress(self, req: &Request, min_size: u64) -> Result<Self> {
+        if self.status_code == 206 || self.headers.has("content-encoding")? {
+            return Ok(self);
+        }
+        let content_type = self.headers.get(CONTENT_TYPE)?.unwrap_or_default();
+        if !is_compressible(&content_type) {
+            return Ok(self);
+        }
+        let size = match &self.body {
+            ResponseBody::Empty => return Ok(self),
+            ResponseBody::Body(bytes) => Some(bytes.len() as u64),
+            ResponseBody::Stream(_) => self
+                .headers
+                .get("content-length")?
+                .and_then(|len| len.trim().parse().ok()),
+        };
+        if matches!(size, Some(size) if size < min_size) {
+            return Ok(self);
+        }
+
+        let res = self.append_vary("Accept-Encoding")?;
+        let accept_encoding = req.headers().get("accept-encoding")?.unwrap_or_default();
+        let available = [
+            ContentEncoding::Brotli,
+            ContentEncoding::Gzip,
+            ContentEncoding::Deflate,
+        ];
+        let encoding = match negotiate_encoding(&accept_encoding, &available) {
+            Some(encoding) => encoding,
+            None => return Ok(res),
+        };
+
+        let mut headers = res.headers.clone();
+        headers.set("content-encoding", encoding.as_str())?;
+        let res = res.with_headers(headers);
+        match encoding {
+            ContentEncoding::Deflate => res.pipe_through(TransformStream::compress(encoding)?),
+            _ => Ok(res),
         }
     }
 
@@ -153,14 +595,148 @@ impl Response {
         self
     }
 
+    /// Send `trailers` after this response's body, e.g. the `grpc-status` of a gRPC-web response.
+    /// The Workers runtime cannot send trailers, so this always returns
+    /// `Error::TrailersUnsupported` rather than silently dropping them. Encode such values in the
+    /// body instead, as gRPC-web allows.
+    pub fn with_trailers(self, trailers: Headers) -> Result<Self> {
+        let _ = trailers;
+        Err(Error::TrailersUnsupported)
+    }
+
+    /// Set this response's `Content-Type` header, replacing any existing value.
+    pub fn with_content_type(mut self, content_type: ContentType) -> Result<Self> {
+        self.headers.set(CONTENT_TYPE, content_type.as_str())?;
+        Ok(self)
+    }
+
+    /// Set this response's `Cache-Control` header from the provided directives, replacing any
+    /// existing value.
+    pub fn with_cache_control(self, cache_control: CacheControl) -> Self {
+        // copying the headers ensures they can be modified, even for a fetched response
+        let mut headers = self.headers.clone();
+        // serialized directives are always ASCII, so `set` cannot reject the header value
+        let _ = headers.set("cache-control", &cache_control.to_string());
+        self.with_headers(headers)
+    }
+
+    /// Set this response's `Cache-Control` header to `no-store`, replacing any existing value, so
+    /// that neither the browser nor any cache along the way keeps a copy of it.
+    pub fn no_store(self) -> Self {
+        self.with_cache_control(CacheControl::new().no_store())
+    }
+
+    /// Set the headers every sensitive response should carry, replacing any existing values:
+    /// `X-Content-Type-Options: nosniff`, `X-Frame-Options: DENY`, `Referrer-Policy: no-referrer`
+    /// and `Cache-Control: no-store`, along with `csp` as the `Content-Security-Policy`, if given.
+    /// ```ignore
+    /// Response::from_html(account_page)?
+    ///     .with_security_headers(Some("default-src 'self'; frame-ancestors 'none'"))
+    /// ```
+    pub fn with_security_headers(self, csp: Option<&str>) -> Result<Self> {
+        // copying the headers ensures they can be modified, even for a fetched response
+        let mut headers = self.headers.clone();
+        headers.set("x-content-type-options", "nosniff")?;
+        headers.set("x-frame-options", "DENY")?;
+        headers.set("referrer-policy", "no-referrer")?;
+        if let Some(csp) = csp {
+            headers.set("content-security-policy", csp)?;
+        }
+        Ok(self.with_headers(headers).no_store())
+    }
+
+    /// Set this response's `Last-Modified` header to `date`, replacing any existing value, so that
+    /// clients can revalidate it with `If-Modified-Since`. See `Response::not_modified_since`.
+    pub fn with_last_modified(self, date: Date) -> Self {
+        // copying the headers ensures they can be modified, even for a fetched response
+        let mut headers = self.headers.clone();
+        // HTTP dates are always ASCII, so `set` cannot reject the header value
+        let _ = headers.set("last-modified", &date.to_utc_string());
+        self.with_headers(headers)
+    }
+
+    /// Allow this response to be read by any origin, setting `Access-Control-Allow-Origin: *`
+    /// along with permissive allowed methods and headers.
+    ///
+    /// **Note:** browsers refuse wildcard CORS headers on credentialed requests (those sent with
+    /// cookies or an `Authorization` header), so this is only suitable for public, uncredentialed
+    /// endpoints.
+    pub fn with_cors_any(self) -> Self {
+        // copying the headers ensures they can be modified, even for a fetched response
+        let mut headers = self.headers.clone();
+        for (name, value) in [
+            ("access-control-allow-origin", "*"),
+            (
+                "access-control-allow-methods",
+                "GET, HEAD, POST, PUT, PATCH, DELETE, OPTIONS",
+            ),
+            ("access-control-allow-headers", "*"),
+        ] {
+            // these header names and values are always valid
+            let _ = headers.set(name, value);
+        }
+        self.with_headers(headers)
+    }
+
+    /// Add `header_name` to this response's `Vary` header, so that caches store a separate
+    /// response for each value of that request header. Names already present, compared
+    /// case-insensitively, are not repeated, and nothing is added once the response varies by `*`.
+    pub fn append_vary(mut self, header_name: &str) -> Result<Self> {
+        let vary = merge_vary(self.headers.get("vary")?.as_deref(), header_name);
+        self.headers.set("vary", &vary)?;
+        Ok(self)
+    }
+
+    /// Append `value` to this response's `name` header, keeping any values it already has, e.g. to
+    /// send several `Link` or `Set-Cookie` headers. The values of a repeated header are sent
+    /// separately, though `Headers::get` joins them with commas.
+    pub fn append_header(self, name: &str, value: &str) -> Result<Self> {
+        // copying the headers ensures they can be modified, even for a fetched response
+        let mut headers = self.headers.clone();
+        headers.append(name, value)?;
+        Ok(self.with_headers(headers))
+    }
+
+    /// Append a `Link` header asking the client to preload `url` as the given destination, e.g.
+    /// `"style"`, `"script"` or `"font"`, before it is discovered in the document.
+    /// ```ignore
+    /// Response::from_html(page)?
+    ///     .preload("/app.css", "style")?
+    ///     .preload("/app.js", "script")
+    /// ```
+    pub fn preload(self, url: &str, as_type: &str) -> Result<Self> {
+        self.append_header("link", &format!("<{}>; rel=preload; as={}", url, as_type))
+    }
+
     /// Set this response's status code.
     /// The Workers platform will reject HTTP status codes outside the range of 200..599 inclusive,
     /// and will throw a JavaScript `RangeError`, returning a response with an HTTP 500 status code.
     pub fn with_status(mut self, status_code: u16) -> Self {
         self.status_code = status_code;
+        self.status_text = None;
         self
     }
 
+    /// Set the reason phrase sent with this response's status code, e.g.
+    /// `Unprocessable Entity` for a `422`. The text belongs to the current status, so set it after
+    /// `with_status`, which clears it. Returns an error if `text` contains characters not allowed
+    /// in a reason phrase, such as line breaks.
+    /// ```ignore
+    /// Response::error("invalid email", 422)?.with_status_text("Unprocessable Entity")
+    /// ```
+    pub fn with_status_text(mut self, text: &str) -> Result<Self> {
+        if !is_reason_phrase(text) {
+            return Err(Error::RustError(format!("invalid status text: {:?}", text)));
+        }
+        self.status_text = Some(text.to_string());
+        Ok(self)
+    }
+
+    /// Get the reason phrase set with `with_status_text`, or received from upstream, if any.
+    pub fn status_text(&self) -> Option<&str> {
+        self.status_text.as_deref()
+    }
+
     /// Read the `Headers` on this response.
     pub fn headers(&self) -> &Headers {
         &self.headers
@@ -170,6 +746,251 @@ impl Response {
     pub fn headers_mut(&mut self) -> &mut Headers {
         &mut self.headers
     }
+
+    /// Call `f` with a reference to this response and return it unchanged, e.g. to log it at the
+    /// end of a chain of builders. Neither `status_code` nor `headers` read the body, so
+    /// inspecting a response with them leaves any body or stream intact.
+    /// ```ignore
+    /// handler(req, ctx)
+    ///     .await?
+    ///     .tap(|res| console_log!("{} {} -> {}", method, path, res.status_code()))
+    /// ```
+    pub fn tap(self, f: impl FnOnce(&Self)) -> Self {
+        f(&self);
+        self
+    }
+}
+
+fn into_readable_stream<S>(stream: S) -> ReadableStream
+where
+    S: TryStream + 'static,
+    S::Ok: Into<Vec<u8>>,
+    S::Error: Into<Error>,
+{
+    let js_stream = stream
+        .map_ok(|chunk| {
+            let chunk: Vec<u8> = chunk.into();
+            JsValue::from(Uint8Array::from(chunk.as_slice()))
+        })
+        .map_err(|e| JsValue::from(e.into()))
+        .into_stream();
+
+    // the stream is polled from the ReadableStream's `pull` callback, which the runtime calls only
+    // while the queue is below its high-water mark of 0, i.e. once the consumer has asked for
+    // another chunk, so chunks are never read ahead of the client
+    ReadableStream::from_stream(js_stream)
+}
+
+pub(crate) fn serialization_error(e: serde_json::Error) -> Error {
+    Error::Serialization(format!("failed to encode data to json: {}", e))
+}
+
+fn shape_json(value: Value, options: &JsonResponseOptions) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .filter(|(_, value)| !(options.skip_nulls && value.is_null()))
+                .map(|(key, value)| {
+                    let key = if options.camel_case {
+                        camel_case(&key)
+                    } else {
+                        key
+                    };
+                    (key, shape_json(value, options))
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| shape_json(item, options))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+fn camel_case(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    let mut upper = false;
+    for c in key.chars() {
+        if c == '_' && !out.is_empty() {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Parse a `Range` header for content of `len` bytes into the inclusive bounds of each range it
+/// asks for, skipping those which cannot be satisfied. Returns `None` for a header which should be
+/// ignored, as it is malformed or uses a unit other than bytes.
+fn parse_ranges(range: &str, len: u64) -> Option<Vec<(u64, u64)>> {
+    let (unit, specs) = range.split_once('=')?;
+    if !unit.trim().eq_ignore_ascii_case("bytes") {
+        return None;
+    }
+
+    let mut ranges = Vec::new();
+    for spec in specs
+        .split(',')
+        .map(str::trim)
+        .filter(|spec| !spec.is_empty())
+    {
+        let (start, end) = spec.split_once('-')?;
+        let (start, end) = (start.trim(), end.trim());
+        let bounds = if start.is_empty() {
+            // a suffix range, for the last `end` bytes
+            let suffix: u64 = end.parse().ok()?;
+            (suffix > 0 && len > 0).then(|| (len.saturating_sub(suffix), len - 1))
+        } else {
+            let start: u64 = start.parse().ok()?;
+            let end = match end {
+                "" => u64::MAX,
+                end => end.parse().ok()?,
+            };
+            if end < start {
+                return None;
+            }
+            (start < len).then(|| (start, end.min(len - 1)))
+        };
+        ranges.extend(bounds);
+    }
+    Some(ranges)
+}
+
+fn content_range(start: u64, end: u64, len: u64) -> String {
+    format!("bytes {}-{}/{}", start, end, len)
+}
+
+/// Encode each of `ranges` of `bytes` as a part of a `multipart/byteranges` body.
+fn multipart_byteranges(
+    bytes: &[u8],
+    ranges: &[(u64, u64)],
+    content_type: &str,
+    boundary: &str,
+) -> Vec<u8> {
+    let len = bytes.len() as u64;
+    let mut body = Vec::new();
+    for (start, end) in ranges {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Type: {}\r\nContent-Range: {}\r\n\r\n",
+                boundary,
+                content_type,
+                content_range(*start, *end, len)
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(&bytes[*start as usize..=*end as usize]);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// Whether `text` is a valid HTTP reason phrase: tabs, spaces and visible characters only.
+fn is_reason_phrase(text: &str) -> bool {
+    text.chars()
+        .all(|c| c == '\t' || c == ' ' || (!c.is_control() && c != '\u{7f}'))
+}
+
+fn merge_vary(existing: Option<&str>, header_name: &str) -> String {
+    let header_name = header_name.trim();
+    let mut names: Vec<&str> = existing
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    if header_name == "*" {
+        return "*".into();
+    }
+    if !names
+        .iter()
+        .any(|name| *name == "*" || name.eq_ignore_ascii_case(header_name))
+    {
+        names.push(header_name);
+    }
+
+    names.join(", ")
+}
+
+fn is_unmodified(last_modified_millis: u64, since_millis: u64) -> bool {
+    last_modified_millis / 1000 <= since_millis / 1000
+}
+
+/// Pass on the chunks of `stream`, erroring once it has produced more than `len` bytes, or fewer
+/// when it ends.
+fn check_length<S>(stream: S, len: u64) -> impl Stream<Item = Result<Vec<u8>>>
+where
+    S: TryStream + 'static,
+    S::Ok: Into<Vec<u8>>,
+    S::Error: Into<Error>,
+{
+    let mismatch = move |actual| Error::ContentLengthMismatch {
+        expected: len,
+        actual,
+    };
+
+    // `None` marks the end of the stream, where the total length is checked
+    let chunks = stream
+        .map_ok(Into::into)
+        .map_err(Into::into)
+        .into_stream()
+        .map(Some)
+        .chain(stream::once(future::ready(None)));
+    chunks.scan(0, move |sent: &mut u64, chunk| {
+        let item = match chunk {
+            Some(Ok(bytes)) => {
+                *sent += bytes.len() as u64;
+                if *sent > len {
+                    Some(Err(mismatch(*sent)))
+                } else {
+                    Some(Ok(bytes))
+                }
+            }
+            Some(Err(e)) => Some(Err(e)),
+            None if *sent != len => Some(Err(mismatch(*sent))),
+            None => None,
+        };
+        future::ready(item)
+    })
+}
+
+#[test]
+fn json_is_shaped_by_options() {
+    let value = serde_json::json!({
+        "user_id": 1,
+        "display_name": null,
+        "_private": "x",
+        "roles": [{ "role_name": "admin", "granted_by": null }],
+    });
+
+    assert_eq!(
+        shape_json(value.clone(), &JsonResponseOptions::default()),
+        value
+    );
+    assert_eq!(
+        shape_json(
+            value,
+            &JsonResponseOptions {
+                skip_nulls: true,
+                camel_case: true,
+            }
+        ),
+        serde_json::json!({
+            "userId": 1,
+            "_private": "x",
+            "roles": [{ "roleName": "admin" }],
+        })
+    );
 }
 
 #[test]
@@ -179,6 +1000,57 @@ fn no_using_invalid_error_status_code() {
     assert!(Response::error("399", 399).is_err());
 }
 
+#[test]
+fn ranges_are_parsed_and_clamped() {
+    assert_eq!(parse_ranges("bytes=0-99", 1000), Some(vec![(0, 99)]));
+    assert_eq!(
+        parse_ranges("bytes=0-99, 200-, -50", 1000),
+        Some(vec![(0, 99), (200, 999), (950, 999)])
+    );
+    assert_eq!(parse_ranges("bytes=900-1200", 1000), Some(vec![(900, 999)]));
+    assert_eq!(parse_ranges("bytes=1000-, -0", 1000), Some(vec![]));
+    assert_eq!(parse_ranges("bytes=5-1", 1000), None);
+    assert_eq!(parse_ranges("items=0-1", 1000), None);
+}
+
+#[test]
+fn multiple_ranges_are_sent_as_parts() {
+    let body = multipart_byteranges(b"hello world", &[(0, 4), (6, 10)], "text/plain", "b");
+    assert_eq!(
+        String::from_utf8(body).unwrap(),
+        "--b\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-4/11\r\n\r\nhello\r\n\
+         --b\r\nContent-Type: text/plain\r\nContent-Range: bytes 6-10/11\r\n\r\nworld\r\n\
+         --b--\r\n"
+    );
+}
+
+#[test]
+fn status_text_must_be_a_reason_phrase() {
+    assert!(is_reason_phrase("Unprocessable Entity"));
+    assert!(is_reason_phrase(""));
+    assert!(!is_reason_phrase("OK\r\nSet-Cookie: a=b"));
+}
+
+#[test]
+fn vary_entries_are_deduplicated() {
+    assert_eq!(merge_vary(None, "Accept"), "Accept");
+    assert_eq!(merge_vary(Some("Accept"), "accept"), "Accept");
+    assert_eq!(
+        merge_vary(Some("Accept,Origin"), "Accept-Encoding"),
+        "Accept, Origin, Accept-Encoding"
+    );
+    assert_eq!(merge_vary(Some("*"), "Accept"), "*");
+    assert_eq!(merge_vary(Some("Accept"), "*"), "*");
+}
+
+#[test]
+fn modification_dates_are_compared_to_the_second() {
+    assert!(is_unmodified(1_630_611_511_000, 1_630_611_511_000));
+    assert!(is_unmodified(1_630_611_511_999, 1_630_611_511_000));
+    assert!(is_unmodified(1_630_611_500_000, 1_630_611_511_000));
+    assert!(!is_unmodified(1_630_611_512_000, 1_630_611_511_999));
+}
+
 pub struct ResponseInit {
     pub status: u16,
     pub headers: Headers,
@@ -195,26 +1067,23 @@ impl From<ResponseInit> for EdgeResponseInit {
 
 impl From<Response> for EdgeResponse {
     fn from(res: Response) -> Self {
+        let mut init: EdgeResponseInit = ResponseInit {
+            status: res.status_code,
+            headers: res.headers,
+        }
+        .into();
+        if let Some(status_text) = &res.status_text {
+            init.status_text(status_text);
+        }
+
         match res.body {
-            ResponseBody::Body(mut bytes) => EdgeResponse::new_with_opt_u8_array_and_init(
-                Some(&mut bytes),
-                &ResponseInit {
-                    status: res.status_code,
-                    headers: res.headers,
-                }
-                .into(),
-            )
-            .unwrap(),
-            ResponseBody::Stream(response) => response,
-            ResponseBody::Empty => EdgeResponse::new_with_opt_str_and_init(
-                None,
-                &ResponseInit {
-                    status: res.status_code,
-                    headers: res.headers,
-                }
-                .into(),
-            )
-            .unwrap(),
+            ResponseBody::Body(mut bytes) => {
+                EdgeResponse::new_with_opt_u8_array_and_init(Some(&mut bytes), &init).unwrap()
+            }
+            ResponseBody::Stream(response) => {
+                EdgeResponse::new_with_opt_stream_and_init(response.body().as_ref(), &init).unwrap()
+            }
+            ResponseBody::Empty => EdgeResponse::new_with_opt_str_and_init(None, &init).unwrap(),
         }
     }
 }
@@ -224,6 +1093,7 @@ impl From<EdgeResponse> for Response {
         Self {
             headers: Headers(res.headers()),
             status_code: res.status(),
+            status_text: Some(res.status_text()).filter(|text| !text.is_empty()),
             body: match res.body() {
                 Some(_) => ResponseBody::Stream(res),
                 None => ResponseBody::Empty,
@@ -231,3 +1101,33 @@ impl From<EdgeResponse> for Response {
         }
     }
 }
+
+#[test]
+fn streamed_lengths_are_checked() {
+    let chunks = |chunks: Vec<&'static [u8]>| {
+        stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| Ok::<_, Error>(chunk.to_vec())),
+        )
+    };
+    let collect = |chunks| {
+        futures::executor::block_on(check_length(chunks, 5).collect::<Vec<_>>())
+            .into_iter()
+            .map(|chunk| chunk.map_err(|e| e.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        collect(chunks(vec![b"abc", b"de"])),
+        vec![Ok(b"abc".to_vec()), Ok(b"de".to_vec())]
+    );
+    assert_eq!(
+        collect(chunks(vec![b"abc"])).pop(),
+        Some(Err("body length 3 does not match content-length 5".into()))
+    );
+    assert_eq!(
+        collect(chunks(vec![b"abc", b"def"])).pop(),
+        Some(Err("body length 6 does not match content-length 5".into()))
+    );
+}
@@ -1,20 +1,43 @@
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::time::Duration;
 
 use futures::{future::LocalBoxFuture, Future};
-use matchit::{Match, Node};
+use matchit::{InsertError, Match, Node};
+use percent_encoding::percent_decode_str;
+use wasm_bindgen::JsValue;
 use worker_kv::KvStore;
 
 use crate::{
+    analytics_engine::AnalyticsEngine,
     durable::ObjectNamespace,
     env::{Env, Secret, Var},
+    error::Error,
+    global::Fetch,
+    headers::Headers,
     http::Method,
+    hyperdrive::Hyperdrive,
+    kv::{ByteStream, GetOptions, KvGetValue},
+    middleware::Middleware,
+    mime::mime_for_extension,
     request::Request,
     response::Response,
+    service::Service,
+    time::Stopwatch,
     Result,
 };
+use worker_sys::{console_error, global::random_uuid, Request as EdgeRequest};
+
+const REQUEST_ID_HEADER: &str = "x-request-id";
+const DATA_TAKEN: &str = "the router's data is only taken by `run`, which consumes the router";
+/// How many times `Router::dispatch` may re-route a request before it is assumed to loop.
+const MAX_DISPATCH_DEPTH: usize = 8;
 
 type HandlerFn<D> = fn(Request, RouteContext<D>) -> Result<Response>;
+type RequestHook<'a> = Box<dyn 'a + Fn(&Request)>;
+type ResponseHook<'a> = Box<dyn 'a + Fn(&Request, &Response, Duration)>;
 type AsyncHandlerFn<'a, D> =
     Rc<dyn 'a + Fn(Request, RouteContext<D>) -> LocalBoxFuture<'a, Result<Response>>>;
 
@@ -23,9 +46,15 @@ type AsyncHandlerFn<'a, D> =
 pub struct RouteParams(HashMap<String, String>);
 
 impl RouteParams {
-    fn get(&self, key: &str) -> Option<&String> {
+    /// Get a URL parameter by the name of its match or wildcard placeholder.
+    pub fn get(&self, key: &str) -> Option<&String> {
         self.0.get(key)
     }
+
+    /// Iterate over the names and values of all URL parameters.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.0.iter()
+    }
 }
 
 enum Handler<'a, D> {
@@ -42,11 +71,85 @@ impl<D> Clone for Handler<'_, D> {
     }
 }
 
+type MiddlewareRef<'a, D> = Rc<dyn 'a + Middleware<D>>;
+
+/// A registered handler, along with the middleware of the groups it was registered in.
+struct Route<'a, D> {
+    handler: Handler<'a, D>,
+    middleware: Vec<MiddlewareRef<'a, D>>,
+    bypass_middleware: bool,
+}
+
+impl<D> Clone for Route<'_, D> {
+    fn clone(&self) -> Self {
+        Self {
+            handler: self.handler.clone(),
+            middleware: self.middleware.clone(),
+            bypass_middleware: self.bypass_middleware,
+        }
+    }
+}
+
+impl<'a, D> Route<'a, D> {
+    async fn call(
+        &self,
+        global: &[MiddlewareRef<'a, D>],
+        req: Request,
+        ctx: RouteContext<D>,
+    ) -> Result<Response> {
+        if !self.bypass_middleware {
+            for middleware in global.iter().chain(&self.middleware) {
+                if let Some(res) = middleware.handle(&req, &ctx).await? {
+                    return Ok(res);
+                }
+            }
+        }
+
+        match &self.handler {
+            Handler::Sync(func) => (func)(req, ctx),
+            Handler::Async(func) => (func)(req, ctx).await,
+        }
+    }
+}
+
+/// Limits on the size of requests, enforced by a `Router` before any route is matched. See
+/// `Router::limits`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// The maximum length of the full URL, in bytes.
+    pub max_url: Option<usize>,
+    /// The maximum total length of all header names and values, in bytes.
+    pub max_headers: Option<usize>,
+    /// The maximum size of the body, in bytes, as set by `Router::body_limit`.
+    pub max_body: Option<u64>,
+}
+
+/// The prefix and middleware applied to routes registered within a `Router::group`.
+struct Scope<'a, D> {
+    prefix: String,
+    middleware: Vec<MiddlewareRef<'a, D>>,
+}
+
 /// A path-based HTTP router supporting exact-match or wildcard placeholders and shared data.
 pub struct Router<'a, D> {
-    handlers: HashMap<Method, Node<Handler<'a, D>>>,
-    or_else_any_method: Node<Handler<'a, D>>,
-    data: D,
+    handlers: HashMap<Method, Node<Route<'a, D>>>,
+    or_else_any_method: Node<Route<'a, D>>,
+    middleware: Vec<MiddlewareRef<'a, D>>,
+    scopes: Vec<Scope<'a, D>>,
+    // only `None` once taken by `run`
+    data: Option<D>,
+    method_override: bool,
+    body_limit: Option<u64>,
+    max_url: Option<usize>,
+    max_headers: Option<usize>,
+    request_ids: bool,
+    auto_options: bool,
+    json_errors: bool,
+    fallback: Option<Box<Router<'a, D>>>,
+    on_request: Vec<RequestHook<'a>>,
+    on_response: Vec<ResponseHook<'a>>,
+    hosts: Vec<(String, Router<'a, D>)>,
+    host_params: HashMap<String, String>,
 }
 
 /// Container for a route's parsed parameters, data, and environment bindings from the Runtime (such
@@ -55,6 +158,10 @@ pub struct RouteContext<D> {
     data: D,
     env: Env,
     params: RouteParams,
+    host_params: RouteParams,
+    request_id: Option<String>,
+    depth: usize,
+    memo: RefCell<HashMap<(String, TypeId), Box<dyn Any>>>,
 }
 
 impl<D> RouteContext<D> {
@@ -69,11 +176,28 @@ impl<D> RouteContext<D> {
         self.env
     }
 
+    /// Whether a binding with the given name is present on this Worker, regardless of its type.
+    pub fn has_binding(&self, name: &str) -> bool {
+        self.env.has_binding(name)
+    }
+
+    /// Get the raw JavaScript object of any binding associated with this Worker, as an escape
+    /// hatch for bindings which are not wrapped yet. See `Env::get_binding`.
+    pub fn get_binding(&self, name: &str) -> Result<JsValue> {
+        self.env.get_binding(name)
+    }
+
     /// Get a Secret value associated with this Worker, should one exist.
     pub fn secret(&self, binding: &str) -> Result<Secret> {
         self.env.secret(binding)
     }
 
+    /// Get a Secret value holding base64-encoded binary key material as bytes. See
+    /// `Env::secret_bytes`.
+    pub fn secret_bytes(&self, binding: &str) -> Result<Vec<u8>> {
+        self.env.secret_bytes(binding)
+    }
+
     /// Get an Environment Variable value associated with this Worker, should one exist.
     pub fn var(&self, binding: &str) -> Result<Var> {
         self.env.var(binding)
@@ -81,7 +205,45 @@ impl<D> RouteContext<D> {
 
     /// Get a KV Namespace associated with this Worker, should one exist.
     pub fn kv(&self, binding: &str) -> Result<KvStore> {
-        KvStore::from_this(&self.env, binding).map_err(From::from)
+        self.env.kv(binding)
+    }
+
+    /// Read a value from a KV Namespace associated with this Worker, with control over its edge
+    /// cache TTL and how it is decoded. See `Env::kv_get_with_options`.
+    pub async fn kv_get_with_options(
+        &self,
+        binding: &str,
+        key: &str,
+        options: &GetOptions,
+    ) -> Result<Option<KvGetValue>> {
+        self.env.kv_get_with_options(binding, key, options).await
+    }
+
+    /// Read a value from a KV Namespace associated with this Worker as a stream of bytes. See
+    /// `Env::kv_get_stream`.
+    pub async fn kv_get_stream(&self, binding: &str, key: &str) -> Result<Option<ByteStream>> {
+        self.env.kv_get_stream(binding, key).await
+    }
+
+    /// Read a binary value from a KV Namespace associated with this Worker. See
+    /// `Env::kv_get_bytes`.
+    pub async fn kv_get_bytes(&self, binding: &str, key: &str) -> Result<Option<Vec<u8>>> {
+        self.env.kv_get_bytes(binding, key).await
+    }
+
+    /// Get an Analytics Engine dataset associated with this Worker, should one exist.
+    pub fn analytics_engine(&self, binding: &str) -> Result<AnalyticsEngine> {
+        self.env.analytics_engine(binding)
+    }
+
+    /// Get a service binding to another Worker associated with this Worker, should one exist.
+    pub fn service(&self, binding: &str) -> Result<Service> {
+        self.env.service(binding)
+    }
+
+    /// Get a Hyperdrive configuration associated with this Worker, should one exist.
+    pub fn hyperdrive(&self, binding: &str) -> Result<Hyperdrive> {
+        self.env.hyperdrive(binding)
     }
 
     /// Get a Durable Object Namespace associated with this Worker, should one exist.
@@ -89,10 +251,76 @@ impl<D> RouteContext<D> {
         self.env.durable_object(binding)
     }
 
+    /// Get the ID of the request being handled, when request IDs are enabled on the `Router`.
+    pub fn request_id(&self) -> Option<&String> {
+        self.request_id.as_ref()
+    }
+
+    /// Execute a Fetch call and receive a Response. When request IDs are enabled on the `Router`,
+    /// the ID of the request being handled is attached to the outbound request as its
+    /// `X-Request-ID` header, correlating the two across Workers and services.
+    pub async fn fetch(&self, fetch: Fetch) -> Result<Response> {
+        let request_id = match &self.request_id {
+            Some(request_id) => request_id,
+            None => return fetch.send().await,
+        };
+
+        // copying the request gives it mutable headers, even when the original was an incoming
+        // request
+        let req = match fetch {
+            Fetch::Url(url) => EdgeRequest::new_with_str(url.as_str())?,
+            Fetch::Request(req) => EdgeRequest::new_with_request(req.inner())?,
+        };
+        req.headers().set(REQUEST_ID_HEADER, request_id)?;
+
+        Fetch::Request(req.into()).send().await
+    }
+
     /// Get a URL parameter parsed by the router, by the name of its match or wildecard placeholder.
     pub fn param(&self, key: &str) -> Option<&String> {
         self.params.get(key)
     }
+
+    /// Get a placeholder matched in the request's host by `Router::host`, e.g. the `tenant` in
+    /// `:tenant.example.com`.
+    pub fn host_param(&self, key: &str) -> Option<&String> {
+        self.host_params.get(key)
+    }
+
+    /// Get the value memoized for `key` while handling this request, or run `init` to produce it.
+    /// Middleware and the handler of a route share the same `RouteContext`, so an expensive value
+    /// needed by both, such as decoded JWT claims, is only computed once. Values are memoized per
+    /// `key` and type, and nothing is memoized if `init` returns an error.
+    /// ```ignore
+    /// let claims: Claims = ctx
+    ///     .get_or_init_async("claims", || decode_jwt(&req, &secret))
+    ///     .await?;
+    /// ```
+    pub async fn get_or_init_async<T, F, Fut>(&self, key: &str, init: F) -> Result<T>
+    where
+        T: Clone + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let slot = (key.to_string(), TypeId::of::<T>());
+        if let Some(value) = self.memo.borrow().get(&slot) {
+            return Ok(value
+                .downcast_ref::<T>()
+                .expect("memoized values are keyed by their type")
+                .clone());
+        }
+
+        // the memo is not borrowed while `init` runs, so it may memoize other values itself
+        let value = init().await?;
+        self.memo.borrow_mut().insert(slot, Box::new(value.clone()));
+        Ok(value)
+    }
+
+    /// Take ownership of the data, `Env` and URL parameters of this `RouteContext` at once, e.g.
+    /// when building custom dispatch on top of the `Router`.
+    pub fn into_inner(self) -> (D, Env, RouteParams) {
+        (self.data, self.env, self.params)
+    }
 }
 
 impl<'a> Router<'a, ()> {
@@ -109,8 +337,179 @@ impl<'a, D: 'a> Router<'a, D> {
         Self {
             handlers: HashMap::new(),
             or_else_any_method: Node::new(),
-            data,
+            middleware: Vec::new(),
+            scopes: Vec::new(),
+            data: Some(data),
+            method_override: false,
+            body_limit: None,
+            max_url: None,
+            max_headers: None,
+            request_ids: false,
+            auto_options: false,
+            json_errors: false,
+            fallback: None,
+            on_request: Vec::new(),
+            on_response: Vec::new(),
+            hosts: Vec::new(),
+            host_params: HashMap::new(),
+        }
+    }
+
+    /// Register middleware to run before the handler of matched routes, in the order it was
+    /// registered. Called on the `Router` itself, the middleware applies to every route. Called
+    /// within `Router::group`, it applies only to the routes registered in that group after it.
+    pub fn middleware(mut self, middleware: impl Middleware<D> + 'a) -> Self {
+        let middleware: MiddlewareRef<'a, D> = Rc::new(middleware);
+        match self.scopes.last_mut() {
+            Some(scope) => scope.middleware.push(middleware),
+            None => self.middleware.push(middleware),
         }
+        self
+    }
+
+    /// Register a group of routes sharing a path prefix, e.g. `/admin`, by building them in the
+    /// `build` callback. Middleware registered within the group applies only to its own routes,
+    /// after any middleware registered on the `Router` or on the groups enclosing it.
+    pub fn group(mut self, prefix: &str, build: impl FnOnce(Self) -> Self) -> Self {
+        let scope = Scope {
+            prefix: self.prefix() + prefix.trim_end_matches('/'),
+            middleware: Vec::new(),
+        };
+        self.scopes.push(scope);
+
+        let mut router = build(self);
+        router.scopes.pop();
+        router
+    }
+
+    /// Register the routes built in the `build` callback with `middleware` attached, without a
+    /// shared path prefix. Only those routes run it, after the middleware registered on the
+    /// `Router` and on any enclosing groups, e.g. to rate-limit a single expensive endpoint.
+    /// ```ignore
+    /// Router::new()
+    ///     .middleware(log_requests)
+    ///     .get("/", index)
+    ///     .with_middleware(rate_limit, |router| router.post_async("/export", export))
+    /// ```
+    pub fn with_middleware(
+        self,
+        middleware: impl Middleware<D> + 'a,
+        build: impl FnOnce(Self) -> Self,
+    ) -> Self {
+        self.group("", |router| build(router.middleware(middleware)))
+    }
+
+    /// Register a callback invoked with each request handled by this `Router`, before it is
+    /// dispatched to any middleware or handler.
+    pub fn on_request(mut self, hook: impl 'a + Fn(&Request)) -> Self {
+        self.on_request.push(Box::new(hook));
+        self
+    }
+
+    /// Register a callback invoked with each request handled by this `Router` once it has been
+    /// answered, along with the response and the time taken to produce it. The body of the
+    /// request is not available to the callback, and when handling the request returns an error,
+    /// the callback receives the `500 Internal Server Error` response sent in its place.
+    /// ```ignore
+    /// Router::new().on_response(|req, res, elapsed| {
+    ///     console_log!("{} {} -> {} in {:?}", req.method().to_string(), req.path(), res.status_code(), elapsed)
+    /// })
+    /// ```
+    pub fn on_response(mut self, hook: impl 'a + Fn(&Request, &Response, Duration)) -> Self {
+        self.on_response.push(Box::new(hook));
+        self
+    }
+
+    /// Dispatch requests whose `Host` matches `host_pattern` to the `sub` router, ignoring any
+    /// port. Each dot-separated label of the pattern is matched case-insensitively, or captured by
+    /// a `:name` placeholder, available from `RouteContext::host_param`. Hosts are tried in the
+    /// order they were registered, and a request whose host matches none of them is dispatched to
+    /// this router's own routes.
+    /// ```ignore
+    /// let tenants = Router::new().get("/", |_, ctx| {
+    ///     Response::ok(format!("tenant {}", ctx.host_param("tenant").unwrap()))
+    /// });
+    /// Router::new().host(":tenant.example.com", tenants).get("/", |_, _| Response::ok("home"))
+    /// ```
+    pub fn host(mut self, host_pattern: &str, sub: Router<'a, D>) -> Self {
+        self.hosts.push((host_pattern.to_string(), sub));
+        self
+    }
+
+    /// Fall back to the `other` router when this one answers a request with `404 Not Found`, so
+    /// that independently built routers can be composed, e.g. the application's routes and those
+    /// of a mounted admin crate. Both routers are run with the same `Env`. Calling `or` again adds
+    /// another fallback after the last, and each router applies its own middleware and settings.
+    pub fn or(mut self, other: Router<'a, D>) -> Self {
+        self.fallback = Some(Box::new(match self.fallback.take() {
+            Some(fallback) => fallback.or(other),
+            None => other,
+        }));
+        self
+    }
+
+    /// Enable or disable request IDs, which are disabled by default. When enabled, each request is
+    /// given the ID in its `X-Request-ID` header, or a newly generated UUID if it has none. The ID
+    /// is available from `RouteContext::request_id`, attached to outbound requests made with
+    /// `RouteContext::fetch`, and echoed back in the `X-Request-ID` header of the response.
+    pub fn request_ids(mut self, enabled: bool) -> Self {
+        self.request_ids = enabled;
+        self
+    }
+
+    /// Reject requests which declare a `Content-Length` larger than `bytes` before any handler is
+    /// run. Requests sent with `Expect: 100-continue` are answered with `417 Expectation Failed`,
+    /// so the client never sends the body, while all others are answered with
+    /// `413 Payload Too Large`. Bodies are also counted as handlers read them, so a request which
+    /// sends more than `bytes` regardless of its `Content-Length` is answered with
+    /// `413 Payload Too Large` as well.
+    pub fn body_limit(mut self, bytes: u64) -> Self {
+        self.body_limit = Some(bytes);
+        self
+    }
+
+    /// Set all of the size limits enforced on requests at once, replacing any set before, including
+    /// the body limit set with `body_limit`. Requests with a longer URL are answered with
+    /// `414 URI Too Long`, and requests with larger headers with
+    /// `431 Request Header Fields Too Large`.
+    /// ```ignore
+    /// Router::new().limits(Limits {
+    ///     max_url: Some(2048),
+    ///     max_headers: Some(16 * 1024),
+    ///     max_body: Some(1024 * 1024),
+    /// })
+    /// ```
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.max_url = limits.max_url;
+        self.max_headers = limits.max_headers;
+        self.body_limit = limits.max_body;
+        self
+    }
+
+    /// Enable or disable automatic `OPTIONS` responses, which are disabled by default. When
+    /// enabled, an `OPTIONS` request to a path with registered routes but no `OPTIONS` handler is
+    /// answered with `204 No Content` and an `Allow` header listing the methods it supports.
+    pub fn auto_options(mut self, enabled: bool) -> Self {
+        self.auto_options = enabled;
+        self
+    }
+
+    /// Enable or disable JSON error responses, which are disabled by default. The responses the
+    /// router builds itself, such as `404 Not Found`, `405 Method Not Allowed` and the
+    /// `500 Internal Server Error` answering a failed handler, are JSON bodies like
+    /// `{"error": "Not Found", "status": 404}` for requests which prefer JSON in their `Accept`
+    /// header. When enabled, they are JSON for every request.
+    pub fn json_errors(mut self, enabled: bool) -> Self {
+        self.json_errors = enabled;
+        self
+    }
+
+    /// Enable or disable method overriding, which is disabled by default. When enabled, POST
+    /// requests carrying an `X-HTTP-Method-Override` header or a `_method` form field are
+    /// dispatched to the PUT, PATCH, or DELETE handler they name. See `Request::method_override`.
+    pub fn method_override(mut self, enabled: bool) -> Self {
+        self.method_override = enabled;
+        self
     }
 
     /// Register an HTTP handler that will exclusively respond to HEAD requests.
@@ -164,9 +563,87 @@ impl<'a, D: 'a> Router<'a, D> {
     /// Register an HTTP handler that will respond to all methods that are not handled explicitly by
     /// other handlers.
     pub fn or_else_any_method(mut self, pattern: &str, func: HandlerFn<D>) -> Self {
-        self.or_else_any_method
-            .insert(pattern, Handler::Sync(func))
-            .unwrap_or_else(|e| panic!("failed to register route for {} pattern: {}", pattern, e));
+        self.add_or_else_handler(pattern, Handler::Sync(func));
+        self
+    }
+
+    /// Register a route redirecting requests for `pattern` to `target`, with `status`, which must
+    /// be `301`, `302`, `303`, `307` or `308`. Placeholders in `target` are replaced with the
+    /// parameters of the same name matched from the path, and left as-is if there is no such
+    /// parameter.
+    ///
+    /// Panics if `status` is not a redirect status, or if the pattern is invalid. See
+    /// `try_redirect` for a version which returns an error instead.
+    /// ```ignore
+    /// Router::new()
+    ///     .redirect("/old/:id", "/new/:id", 301)
+    ///     .redirect("/docs/*path", "https://docs.example.com/*path", 308)
+    /// ```
+    pub fn redirect(self, pattern: &str, target: &str, status: u16) -> Self {
+        match self.try_redirect(pattern, target, status) {
+            Ok(router) => router,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Register a route redirecting requests for `pattern` to `target` like `redirect`, returning
+    /// an error instead of panicking if `status` is not a redirect status or the pattern is
+    /// invalid.
+    pub fn try_redirect(self, pattern: &str, target: &str, status: u16) -> Result<Self> {
+        if !matches!(status, 301 | 302 | 303 | 307 | 308) {
+            return Err(Error::RustError(format!(
+                "cannot redirect `{}` with status {}, which is not 301, 302, 303, 307 or 308",
+                pattern, status
+            )));
+        }
+
+        let target = target.to_string();
+        let handler = Handler::Async(Rc::new(move |_, ctx: RouteContext<D>| {
+            let location = interpolate_params(&target, &ctx.params);
+            Box::pin(async move {
+                let mut headers = Headers::new();
+                headers.set("location", &location)?;
+                Ok(Response::empty()?.with_status(status).with_headers(headers))
+            })
+        }));
+        self.try_add_handler(pattern, handler, Some(Method::all()))
+    }
+
+    /// Register a health check at `path`, answering `GET` and `HEAD` requests with `200 OK` and
+    /// `body` as plain text. The health check bypasses all middleware, so it stays cheap and is
+    /// not rejected by e.g. authentication middleware.
+    /// ```ignore
+    /// Router::new().health("/healthz", "ok").middleware(require_auth)
+    /// ```
+    pub fn health(mut self, path: &str, body: &str) -> Self {
+        let body = body.to_string();
+        let handler = Handler::Async(Rc::new(move |_, _| {
+            let res = Response::ok(body.clone());
+            Box::pin(async move { res })
+        }));
+        if let Err(failure) =
+            self.insert_route(path, handler, Some(vec![Method::Get, Method::Head]), true)
+        {
+            panic!("{}", failure);
+        }
+        self
+    }
+
+    /// Serve `bytes` as the site's icon at `/favicon.ico`, which browsers request on their own,
+    /// allowing it to be cached for a day. Like `health`, the icon bypasses all middleware.
+    pub fn favicon(mut self, bytes: Vec<u8>) -> Self {
+        let handler = Handler::Async(Rc::new(move |_, _| {
+            let res = favicon_response(bytes.clone());
+            Box::pin(async move { res })
+        }));
+        if let Err(failure) = self.insert_route(
+            "/favicon.ico",
+            handler,
+            Some(vec![Method::Get, Method::Head]),
+            true,
+        ) {
+            panic!("{}", failure);
+        }
         self
     }
 
@@ -296,89 +773,929 @@ impl<'a, D: 'a> Router<'a, D> {
     where
         T: Future<Output = Result<Response>> + 'a,
     {
-        self.or_else_any_method
-            .insert(
-                pattern,
-                Handler::Async(Rc::new(move |req, route| Box::pin(func(req, route)))),
-            )
-            .unwrap_or_else(|e| panic!("failed to register route for {} pattern: {}", pattern, e));
+        self.add_or_else_handler(
+            pattern,
+            Handler::Async(Rc::new(move |req, route| Box::pin(func(req, route)))),
+        );
         self
     }
 
+    /// Register an HTTP handler that will exclusively respond to HEAD requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    pub fn try_head(self, pattern: &str, func: HandlerFn<D>) -> Result<Self> {
+        self.try_add_handler(pattern, Handler::Sync(func), Some(vec![Method::Head]))
+    }
+
+    /// Register an HTTP handler that will exclusively respond to GET requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    pub fn try_get(self, pattern: &str, func: HandlerFn<D>) -> Result<Self> {
+        self.try_add_handler(pattern, Handler::Sync(func), Some(vec![Method::Get]))
+    }
+
+    /// Register an HTTP handler that will exclusively respond to POST requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    pub fn try_post(self, pattern: &str, func: HandlerFn<D>) -> Result<Self> {
+        self.try_add_handler(pattern, Handler::Sync(func), Some(vec![Method::Post]))
+    }
+
+    /// Register an HTTP handler that will exclusively respond to PUT requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    pub fn try_put(self, pattern: &str, func: HandlerFn<D>) -> Result<Self> {
+        self.try_add_handler(pattern, Handler::Sync(func), Some(vec![Method::Put]))
+    }
+
+    /// Register an HTTP handler that will exclusively respond to PATCH requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    pub fn try_patch(self, pattern: &str, func: HandlerFn<D>) -> Result<Self> {
+        self.try_add_handler(pattern, Handler::Sync(func), Some(vec![Method::Patch]))
+    }
+
+    /// Register an HTTP handler that will exclusively respond to DELETE requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    pub fn try_delete(self, pattern: &str, func: HandlerFn<D>) -> Result<Self> {
+        self.try_add_handler(pattern, Handler::Sync(func), Some(vec![Method::Delete]))
+    }
+
+    /// Register an HTTP handler that will exclusively respond to OPTIONS requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    pub fn try_options(self, pattern: &str, func: HandlerFn<D>) -> Result<Self> {
+        self.try_add_handler(pattern, Handler::Sync(func), Some(vec![Method::Options]))
+    }
+
+    /// Register an HTTP handler that will respond to any requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    pub fn try_on(self, pattern: &str, func: HandlerFn<D>) -> Result<Self> {
+        self.try_add_handler(pattern, Handler::Sync(func), Some(Method::all()))
+    }
+
+    /// Register an HTTP handler that will respond to all methods that are not handled explicitly by
+    /// other handlers, returning an error instead of panicking if the pattern is
+    /// invalid or conflicts with a registered route.
+    pub fn try_or_else_any_method(self, pattern: &str, func: HandlerFn<D>) -> Result<Self> {
+        self.try_add_handler(pattern, Handler::Sync(func), None)
+    }
+
+    /// Register an HTTP handler that will exclusively respond to HEAD requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    /// Enables the use of `async/await` syntax in the callback.
+    pub fn try_head_async<T>(
+        self,
+        pattern: &str,
+        func: fn(Request, RouteContext<D>) -> T,
+    ) -> Result<Self>
+    where
+        T: Future<Output = Result<Response>> + 'a,
+    {
+        self.try_add_handler(
+            pattern,
+            Handler::Async(Rc::new(move |req, info| Box::pin(func(req, info)))),
+            Some(vec![Method::Head]),
+        )
+    }
+
+    /// Register an HTTP handler that will exclusively respond to GET requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    /// Enables the use of `async/await` syntax in the callback.
+    pub fn try_get_async<T>(
+        self,
+        pattern: &str,
+        func: fn(Request, RouteContext<D>) -> T,
+    ) -> Result<Self>
+    where
+        T: Future<Output = Result<Response>> + 'a,
+    {
+        self.try_add_handler(
+            pattern,
+            Handler::Async(Rc::new(move |req, info| Box::pin(func(req, info)))),
+            Some(vec![Method::Get]),
+        )
+    }
+
+    /// Register an HTTP handler that will exclusively respond to POST requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    /// Enables the use of `async/await` syntax in the callback.
+    pub fn try_post_async<T>(
+        self,
+        pattern: &str,
+        func: fn(Request, RouteContext<D>) -> T,
+    ) -> Result<Self>
+    where
+        T: Future<Output = Result<Response>> + 'a,
+    {
+        self.try_add_handler(
+            pattern,
+            Handler::Async(Rc::new(move |req, info| Box::pin(func(req, info)))),
+            Some(vec![Method::Post]),
+        )
+    }
+
+    /// Register an HTTP handler that will exclusively respond to PUT requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    /// Enables the use of `async/await` syntax in the callback.
+    pub fn try_put_async<T>(
+        self,
+        pattern: &str,
+        func: fn(Request, RouteContext<D>) -> T,
+    ) -> Result<Self>
+    where
+        T: Future<Output = Result<Response>> + 'a,
+    {
+        self.try_add_handler(
+            pattern,
+            Handler::Async(Rc::new(move |req, info| Box::pin(func(req, info)))),
+            Some(vec![Method::Put]),
+        )
+    }
+
+    /// Register an HTTP handler that will exclusively respond to PATCH requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    /// Enables the use of `async/await` syntax in the callback.
+    pub fn try_patch_async<T>(
+        self,
+        pattern: &str,
+        func: fn(Request, RouteContext<D>) -> T,
+    ) -> Result<Self>
+    where
+        T: Future<Output = Result<Response>> + 'a,
+    {
+        self.try_add_handler(
+            pattern,
+            Handler::Async(Rc::new(move |req, info| Box::pin(func(req, info)))),
+            Some(vec![Method::Patch]),
+        )
+    }
+
+    /// Register an HTTP handler that will exclusively respond to DELETE requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    /// Enables the use of `async/await` syntax in the callback.
+    pub fn try_delete_async<T>(
+        self,
+        pattern: &str,
+        func: fn(Request, RouteContext<D>) -> T,
+    ) -> Result<Self>
+    where
+        T: Future<Output = Result<Response>> + 'a,
+    {
+        self.try_add_handler(
+            pattern,
+            Handler::Async(Rc::new(move |req, info| Box::pin(func(req, info)))),
+            Some(vec![Method::Delete]),
+        )
+    }
+
+    /// Register an HTTP handler that will exclusively respond to OPTIONS requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    /// Enables the use of `async/await` syntax in the callback.
+    pub fn try_options_async<T>(
+        self,
+        pattern: &str,
+        func: fn(Request, RouteContext<D>) -> T,
+    ) -> Result<Self>
+    where
+        T: Future<Output = Result<Response>> + 'a,
+    {
+        self.try_add_handler(
+            pattern,
+            Handler::Async(Rc::new(move |req, info| Box::pin(func(req, info)))),
+            Some(vec![Method::Options]),
+        )
+    }
+
+    /// Register an HTTP handler that will respond to any requests, returning an error instead
+    /// of panicking if the pattern is invalid or conflicts with a registered route.
+    /// Enables the use of `async/await` syntax in the callback.
+    pub fn try_on_async<T>(
+        self,
+        pattern: &str,
+        func: fn(Request, RouteContext<D>) -> T,
+    ) -> Result<Self>
+    where
+        T: Future<Output = Result<Response>> + 'a,
+    {
+        self.try_add_handler(
+            pattern,
+            Handler::Async(Rc::new(move |req, info| Box::pin(func(req, info)))),
+            Some(Method::all()),
+        )
+    }
+
+    /// Register an HTTP handler that will respond to all methods that are not handled explicitly by
+    /// other handlers, returning an error instead of panicking if the pattern is
+    /// invalid or conflicts with a registered route. Enables the use of `async/await` syntax in
+    /// the callback.
+    pub fn try_or_else_any_method_async<T>(
+        self,
+        pattern: &str,
+        func: fn(Request, RouteContext<D>) -> T,
+    ) -> Result<Self>
+    where
+        T: Future<Output = Result<Response>> + 'a,
+    {
+        self.try_add_handler(
+            pattern,
+            Handler::Async(Rc::new(move |req, info| Box::pin(func(req, info)))),
+            None,
+        )
+    }
+
+    fn prefix(&self) -> String {
+        self.scopes
+            .last()
+            .map(|scope| scope.prefix.clone())
+            .unwrap_or_default()
+    }
+
+    fn route(&self, func: Handler<'a, D>) -> Route<'a, D> {
+        Route {
+            handler: func,
+            middleware: self
+                .scopes
+                .iter()
+                .flat_map(|scope| scope.middleware.iter().cloned())
+                .collect(),
+            bypass_middleware: false,
+        }
+    }
+
+    fn add_or_else_handler(&mut self, pattern: &str, func: Handler<'a, D>) {
+        if let Err(failure) = self.insert_route(pattern, func, None, false) {
+            panic!("{}", failure);
+        }
+    }
+
     fn add_handler(&mut self, pattern: &str, func: Handler<'a, D>, methods: Vec<Method>) {
+        if let Err(failure) = self.insert_route(pattern, func, Some(methods), false) {
+            panic!("{}", failure);
+        }
+    }
+
+    fn try_add_handler(
+        mut self,
+        pattern: &str,
+        func: Handler<'a, D>,
+        methods: Option<Vec<Method>>,
+    ) -> Result<Self> {
+        self.insert_route(pattern, func, methods, false)
+            .map_err(Error::RouteRegistration)?;
+        Ok(self)
+    }
+
+    /// Insert the route for `pattern` into the handlers of each method, or into the handlers for
+    /// any method not handled explicitly when `methods` is `None`. The pattern, including the
+    /// prefix of any enclosing group, is validated first.
+    fn insert_route(
+        &mut self,
+        pattern: &str,
+        func: Handler<'a, D>,
+        methods: Option<Vec<Method>>,
+        bypass_middleware: bool,
+    ) -> std::result::Result<(), RouteRegistrationError> {
+        let pattern = self.prefix() + pattern;
+        if let Err(error) = validate_pattern(&pattern) {
+            return Err(RouteRegistrationError {
+                method: methods.and_then(|methods| methods.first().cloned()),
+                pattern,
+                reason: error,
+            });
+        }
+
+        let mut route = self.route(func);
+        route.bypass_middleware = bypass_middleware;
+        let methods = match methods {
+            Some(methods) => methods,
+            None => {
+                return self
+                    .or_else_any_method
+                    .insert(&pattern, route)
+                    .map_err(|error| RouteRegistrationError {
+                        method: None,
+                        pattern,
+                        reason: error.into(),
+                    });
+            }
+        };
+
         for method in methods {
-            self.handlers
+            let inserted = self
+                .handlers
                 .entry(method.clone())
                 .or_insert_with(Node::new)
-                .insert(pattern, func.clone())
-                .unwrap_or_else(|e| {
-                    panic!(
-                        "failed to register {:?} route for {} pattern: {}",
-                        method, pattern, e
-                    )
+                .insert(&pattern, route.clone());
+            if let Err(error) = inserted {
+                return Err(RouteRegistrationError {
+                    method: Some(method),
+                    pattern,
+                    reason: error.into(),
                 });
+            }
         }
+
+        Ok(())
     }
 
     /// Handle the request provided to the `Router` and return a `Future`.
-    pub async fn run(self, req: Request, env: Env) -> Result<Response> {
-        let (handlers, data, or_else_any_method_handler) = self.split();
+    ///
+    /// The router never reads the request body itself, so a client sending `Expect: 100-continue`
+    /// is not left waiting: the runtime answers with `100 Continue` once a handler first reads the
+    /// body. Requests with any other `Expect` value are answered with `417 Expectation Failed`.
+    ///
+    /// Requests whose path contains a `..` segment once percent-decoded, e.g.
+    /// `/static/..%2F..%2Fsecret`, are answered with `400 Bad Request` before any route is matched,
+    /// so that wildcard parameters can't be used to escape a served prefix.
+    ///
+    /// A handler returning `Error::InvalidUtf8` or `Error::ContentLengthMismatch`, for a body which
+    /// is not valid UTF-8 or does not match its `Content-Length`, is answered with
+    /// `400 Bad Request`, one returning `Error::BodyTooLarge` with `413 Payload Too Large`, and one
+    /// returning `Error::UnsupportedMediaType` with `415 Unsupported Media Type`. One returning
+    /// `Error::Json` with a `4xx` status, such as an invalid body rejected by
+    /// `Request::json_strict`, is answered with its message and status.
+    /// Any other error returned while handling the request is logged with `console_error!` and
+    /// answered with `500 Internal Server Error`, keeping the details of the error out of the
+    /// response.
+    pub async fn run(mut self, req: Request, env: Env) -> Result<Response> {
+        let on_request = std::mem::take(&mut self.on_request);
+        let on_response = std::mem::take(&mut self.on_response);
+        let json_errors = self.json_errors;
+
+        observe(&on_request, &on_response, json_errors, req, move |req| {
+            self.serve_owned(req, env)
+        })
+        .await
+    }
+
+    /// Handle the request provided to the `Router` like `run`, without consuming it. This allows a
+    /// router to be built once per isolate and reused for every request it handles, rather than
+    /// rebuilt for each. Every request is given a clone of the router's data.
+    /// ```ignore
+    /// thread_local! {
+    ///     static ROUTER: &'static Router<'static, ()> = Box::leak(Box::new(build_router()));
+    /// }
+    ///
+    /// #[event(fetch)]
+    /// pub async fn main(req: Request, env: Env) -> Result<Response> {
+    ///     let router = ROUTER.with(|router| *router);
+    ///     router.run_ref(req, env).await
+    /// }
+    /// ```
+    pub async fn run_ref(&self, req: Request, env: Env) -> Result<Response>
+    where
+        D: Clone,
+    {
+        self.run_shared(req, env, self.host_params.clone()).await
+    }
+
+    async fn run_shared(
+        &self,
+        req: Request,
+        env: Env,
+        host_params: HashMap<String, String>,
+    ) -> Result<Response>
+    where
+        D: Clone,
+    {
+        observe(
+            &self.on_request,
+            &self.on_response,
+            self.json_errors,
+            req,
+            |req| self.route_shared(req, env, host_params, 0),
+        )
+        .await
+    }
+
+    /// Serve the request like `run_shared`, without running the hooks, `depth` being the number
+    /// of `Router::dispatch` calls it was re-routed through.
+    async fn route_shared(
+        &self,
+        req: Request,
+        env: Env,
+        host_params: HashMap<String, String>,
+        depth: usize,
+    ) -> Result<Response>
+    where
+        D: Clone,
+    {
+        let data = self.data.clone().expect(DATA_TAKEN);
+        match self.serve(req, env, data, host_params, depth).await? {
+            Served::Response(res) => Ok(res),
+            Served::Host(i, params, req, env) => {
+                let sub = &self.hosts[i].1;
+                if depth == 0 {
+                    // boxed, as the sub router may itself route by host
+                    Box::pin(sub.run_shared(req, env, params)).await
+                } else {
+                    Box::pin(sub.route_shared(req, env, params, depth)).await
+                }
+            }
+            Served::Fallback(req, env) => {
+                let fallback = self.fallback.as_ref().expect("a fallback was matched");
+                let host_params = fallback.host_params.clone();
+                if depth == 0 {
+                    // boxed, as the fallback may itself have fallbacks
+                    Box::pin(fallback.run_shared(req, env, host_params)).await
+                } else {
+                    Box::pin(fallback.route_shared(req, env, host_params, depth)).await
+                }
+            }
+        }
+    }
+
+    /// Route a request from within one of this router's handlers, e.g. to rewrite its URL or to
+    /// pick a variant for an A/B test without a redirect visible to the client. The router's
+    /// hooks are not run again, and a request re-routed more than 8 times, such as by a rewrite
+    /// to its own path, is answered with `508 Loop Detected`.
+    ///
+    /// The router must outlive the handler, which is the case for one built once per isolate and
+    /// run with `run_ref`.
+    /// ```ignore
+    /// thread_local! {
+    ///     static ROUTER: &'static Router<'static, ()> = Box::leak(Box::new(build_router()));
+    /// }
+    ///
+    /// router.get_async("/old/*rest", |req, ctx| async move {
+    ///     let router = ROUTER.with(|router| *router);
+    ///     let mut url = req.url()?;
+    ///     url.set_path(&format!("/new/{}", ctx.param("rest").unwrap()));
+    ///     let req = Request::new_with_init(url.as_str(), RequestInit::new().with_method(req.method()))?;
+    ///     router.dispatch(req, &ctx).await
+    /// })
+    /// ```
+    pub async fn dispatch<E>(&self, req: Request, ctx: &RouteContext<E>) -> Result<Response>
+    where
+        D: Clone,
+    {
+        if ctx.depth >= MAX_DISPATCH_DEPTH {
+            let json_errors =
+                self.json_errors || prefers_json(req.headers().get("accept")?.as_deref());
+            return error_response(json_errors, "Loop Detected", 508);
+        }
+        self.route_shared(
+            req,
+            ctx.env.clone(),
+            self.host_params.clone(),
+            ctx.depth + 1,
+        )
+        .await
+    }
+
+    async fn serve_owned(mut self, req: Request, env: Env) -> Result<Response> {
+        let data = self.data.take().expect(DATA_TAKEN);
+        let host_params = std::mem::take(&mut self.host_params);
+        match self.serve(req, env, data, host_params, 0).await? {
+            Served::Response(res) => Ok(res),
+            Served::Host(i, params, req, env) => {
+                let (_, mut sub) = self.hosts.swap_remove(i);
+                sub.host_params = params;
+                // boxed, as the sub router may itself route by host
+                Box::pin(sub.run(req, env)).await
+            }
+            Served::Fallback(req, env) => {
+                let fallback = self.fallback.take().expect("a fallback was matched");
+                // boxed, as the fallback may itself have fallbacks
+                Box::pin(fallback.run(req, env)).await
+            }
+        }
+    }
+
+    /// Serve the request with this router's own routes, or decide which host router or fallback
+    /// should serve it instead, leaving it to the caller to run them.
+    async fn serve(
+        &self,
+        mut req: Request,
+        env: Env,
+        data: D,
+        host_params: HashMap<String, String>,
+        depth: usize,
+    ) -> Result<Served> {
+        if !self.hosts.is_empty() {
+            let host = req.host().unwrap_or_default();
+            for (i, (pattern, _)) in self.hosts.iter().enumerate() {
+                if let Some(params) = match_host(pattern, &host) {
+                    return Ok(Served::Host(i, params, req, env));
+                }
+            }
+        }
+
+        let fallback = match self.fallback {
+            Some(_) => Some((req.clone()?, env.clone())),
+            None => None,
+        };
+
+        req.set_body_limit(self.body_limit);
+
+        let request_id = if self.request_ids {
+            Some(match req.headers().get(REQUEST_ID_HEADER)? {
+                Some(request_id) => request_id,
+                None => random_uuid(),
+            })
+        } else {
+            None
+        };
+
+        let json_errors = self.json_errors || prefers_json(req.headers().get("accept")?.as_deref());
+        let res = match self
+            .serve_routes(req, env, data, host_params, request_id.clone(), depth)
+            .await
+        {
+            Err(e) => match client_error(&e) {
+                Some((msg, status)) => error_response(json_errors, &msg, status),
+                None => Err(e),
+            },
+            res => res,
+        }?;
+
+        if let Some((req, env)) = fallback {
+            if res.status_code() == 404 {
+                return Ok(Served::Fallback(req, env));
+            }
+        }
+
+        match request_id {
+            Some(request_id) => {
+                let mut headers = res.headers().clone();
+                headers.set(REQUEST_ID_HEADER, &request_id)?;
+                Ok(Served::Response(res.with_headers(headers)))
+            }
+            None => Ok(Served::Response(res)),
+        }
+    }
+
+    async fn serve_routes(
+        &self,
+        req: Request,
+        env: Env,
+        data: D,
+        host_params: HashMap<String, String>,
+        request_id: Option<String>,
+        depth: usize,
+    ) -> Result<Response> {
+        let json_errors = self.json_errors || prefers_json(req.headers().get("accept")?.as_deref());
+        if matches!(self.max_url, Some(limit) if req.inner().url().len() > limit) {
+            return error_response(json_errors, "URI Too Long", 414);
+        }
+        if let Some(limit) = self.max_headers {
+            let size: usize = req
+                .headers()
+                .entries()
+                .map(|(name, value)| name.len() + value.len())
+                .sum();
+            if size > limit {
+                return error_response(json_errors, "Request Header Fields Too Large", 431);
+            }
+        }
+
+        let expects_continue = match req.headers().get("expect")? {
+            Some(expect) if expect.eq_ignore_ascii_case("100-continue") => true,
+            Some(_) => return error_response(json_errors, "Expectation Failed", 417),
+            None => false,
+        };
+
+        if is_traversal(&req.path()) {
+            return error_response(json_errors, "Bad Request", 400);
+        }
+
+        if let Some(limit) = self.body_limit {
+            if matches!(req.content_length(), Some(len) if len > limit) {
+                if expects_continue {
+                    return error_response(json_errors, "Expectation Failed", 417);
+                }
+                return error_response(json_errors, "Payload Too Large", 413);
+            }
+        }
 
-        if let Some(handlers) = handlers.get(&req.method()) {
+        let method = if self.method_override {
+            req.method_override().await?.unwrap_or_else(|| req.method())
+        } else {
+            req.method()
+        };
+
+        if let Some(handlers) = self.handlers.get(&method) {
             if let Ok(Match { value, params }) = handlers.at(&req.path()) {
                 let route_info = RouteContext {
                     data,
                     env,
                     params: params.into(),
+                    host_params: RouteParams(host_params),
+                    request_id,
+                    depth,
+                    memo: RefCell::default(),
                 };
-                return match value {
-                    Handler::Sync(func) => (func)(req, route_info),
-                    Handler::Async(func) => (func)(req, route_info).await,
-                };
+                return value.call(&self.middleware, req, route_info).await;
             }
         }
 
-        for method in Method::all() {
-            if method == Method::Head || method == Method::Options || method == Method::Trace {
-                continue;
-            }
-            if let Some(handlers) = handlers.get(&method) {
-                if let Ok(Match { .. }) = handlers.at(&req.path()) {
-                    return Response::error("Method Not Allowed", 405);
-                }
-            }
+        let allowed = allowed_methods(&self.handlers, &req.path());
+        if self.auto_options && method == Method::Options && !allowed.is_empty() {
+            let mut headers = Headers::new();
+            headers.set("allow", &allow_header(&allowed, true))?;
+            return Ok(Response::empty()?.with_status(204).with_headers(headers));
+        }
+
+        if allowed
+            .iter()
+            .any(|method| !matches!(method, Method::Head | Method::Options | Method::Trace))
+        {
+            let mut res = error_response(json_errors, "Method Not Allowed", 405)?;
+            res.headers_mut()
+                .set("allow", &allow_header(&allowed, self.auto_options))?;
+            return Ok(res);
         }
 
-        if let Ok(Match { value, params }) = or_else_any_method_handler.at(&req.path()) {
+        if let Ok(Match { value, params }) = self.or_else_any_method.at(&req.path()) {
             let route_info = RouteContext {
                 data,
                 env,
                 params: params.into(),
+                host_params: RouteParams(host_params),
+                request_id,
+                depth,
+                memo: RefCell::default(),
             };
-            return match value {
-                Handler::Sync(func) => (func)(req, route_info),
-                Handler::Async(func) => (func)(req, route_info).await,
-            };
+            return value.call(&self.middleware, req, route_info).await;
         }
 
-        Response::error("Not Found", 404)
+        error_response(json_errors, "Not Found", 404)
     }
 }
 
-type NodeWithHandlers<'a, D> = Node<Handler<'a, D>>;
+/// How a request is served, as decided by `Router::serve`.
+enum Served {
+    Response(Response),
+    /// By the host router at the given index, with the values of its pattern's placeholders.
+    Host(usize, HashMap<String, String>, Request, Env),
+    /// By the fallback, given a copy of the request made before this router served it.
+    Fallback(Request, Env),
+}
 
-impl<'a, D: 'a> Router<'a, D> {
-    fn split(
-        self,
-    ) -> (
-        HashMap<Method, NodeWithHandlers<'a, D>>,
-        D,
-        NodeWithHandlers<'a, D>,
-    ) {
-        (self.handlers, self.data, self.or_else_any_method)
+/// Run the `Router::on_request` and `Router::on_response` hooks around `serve`, answering an error
+/// returned by it with `500 Internal Server Error`.
+async fn observe<F, Fut>(
+    on_request: &[RequestHook<'_>],
+    on_response: &[ResponseHook<'_>],
+    json_errors: bool,
+    req: Request,
+    serve: F,
+) -> Result<Response>
+where
+    F: FnOnce(Request) -> Fut,
+    Fut: Future<Output = Result<Response>>,
+{
+    for hook in on_request {
+        hook(&req);
+    }
+    let observed = if on_response.is_empty() {
+        None
+    } else {
+        Some(req.without_body()?)
+    };
+
+    let json_errors = json_errors || prefers_json(req.headers().get("accept")?.as_deref());
+    let method = req.method();
+    let path = req.path();
+
+    let stopwatch = Stopwatch::new();
+    let res = match serve(req).await {
+        Ok(res) => res,
+        Err(e) => {
+            console_error!("{} {} failed: {}", method.to_string(), path, e);
+            error_response(json_errors, "Internal Server Error", 500)?
+        }
+    };
+
+    if let Some(observed) = observed {
+        let elapsed = stopwatch.elapsed();
+        for hook in on_response {
+            hook(&observed, &res, elapsed);
+        }
+    }
+
+    Ok(res)
+}
+
+fn favicon_response(bytes: Vec<u8>) -> Result<Response> {
+    let mut headers = Headers::new();
+    headers.set("content-type", mime_for_extension("ico"))?;
+    headers.set("cache-control", "public, max-age=86400")?;
+    Ok(Response::from_bytes(bytes)?.with_headers(headers))
+}
+
+/// Replace each `:name` or `*name` placeholder in `target` with the route parameter of that name.
+fn interpolate_params(target: &str, params: &RouteParams) -> String {
+    let mut out = String::with_capacity(target.len());
+    let mut rest = target;
+    while let Some(i) = rest.find([':', '*']) {
+        out.push_str(&rest[..i + 1]);
+        let after = &rest[i + 1..];
+        let len = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(after.len());
+        if let Some(value) = params.get(&after[..len]) {
+            out.pop();
+            out.push_str(value);
+        } else {
+            out.push_str(&after[..len]);
+        }
+        rest = &after[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Whether `path` tries to climb out of the directory it names with a `..` segment, once any
+/// percent-encoded dots and slashes have been decoded. The runtime already resolves literal `..`
+/// segments in the URL, but encoded slashes such as `..%2F..%2Fsecret` survive into wildcard
+/// parameters, where a handler serving files from a prefix would otherwise follow them.
+fn is_traversal(path: &str) -> bool {
+    percent_decode_str(path)
+        .decode_utf8_lossy()
+        .split(['/', '\\'])
+        .any(|segment| segment == "..")
+}
+
+/// Match `host`, with any port removed, against a `Router::host` pattern, returning the values of
+/// its placeholders.
+fn match_host(pattern: &str, host: &str) -> Option<HashMap<String, String>> {
+    let host = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    };
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+
+    let patterns: Vec<&str> = pattern.split('.').collect();
+    let labels: Vec<&str> = host.split('.').collect();
+    if patterns.len() != labels.len() {
+        return None;
+    }
+
+    let mut params = HashMap::new();
+    for (pattern, label) in patterns.into_iter().zip(labels) {
+        match pattern.strip_prefix(':') {
+            Some(name) if !label.is_empty() => {
+                params.insert(name.to_string(), label.to_string());
+            }
+            None if pattern.eq_ignore_ascii_case(label) => {}
+            _ => return None,
+        }
+    }
+    Some(params)
+}
+
+/// Whether the client asked for JSON in its `Accept` header, in which case the errors produced by
+/// the router itself are sent as JSON, see `Response::error_json`.
+pub(crate) fn prefers_json(accept: Option<&str>) -> bool {
+    accept
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|media_range| media_range.split(';').next())
+        .map(str::trim)
+        .any(|media_type| media_type == "application/json" || media_type.ends_with("+json"))
+}
+
+/// The message and status of the response answering an error caused by the client, such as a
+/// body which is too large, or `None` if the error is the Worker's own.
+fn client_error(error: &Error) -> Option<(String, u16)> {
+    match error {
+        Error::InvalidUtf8 | Error::ContentLengthMismatch { .. } => {
+            Some(("Bad Request".into(), 400))
+        }
+        Error::BodyTooLarge { .. } => Some(("Payload Too Large".into(), 413)),
+        Error::UnsupportedMediaType => Some(("Unsupported Media Type".into(), 415)),
+        Error::Json((msg, status)) if (400..=499).contains(status) => Some((msg.clone(), *status)),
+        _ => None,
+    }
+}
+
+fn error_response(json: bool, message: &str, status: u16) -> Result<Response> {
+    if json {
+        Response::error_json(message, status)
+    } else {
+        Response::error(message, status)
+    }
+}
+
+/// Why a route could not be registered, as reported by `RouteRegistrationError`.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RouteError {
+    /// The pattern overlaps the previously registered pattern `with`.
+    Conflict { with: String },
+    /// The pattern was rejected by the route matcher for another reason.
+    Insert(InsertError),
+    /// The pattern is empty.
+    Empty,
+    /// The pattern does not start with `/`.
+    NoLeadingSlash,
+    /// The parameter with this name appears more than once in the pattern.
+    DuplicateParam(String),
+}
+
+impl From<InsertError> for RouteError {
+    fn from(error: InsertError) -> Self {
+        match error {
+            InsertError::Conflict { with } => RouteError::Conflict { with },
+            error => RouteError::Insert(error),
+        }
+    }
+}
+
+/// Reject patterns which `matchit` would accept, but which could not be matched as intended.
+fn validate_pattern(pattern: &str) -> std::result::Result<(), RouteError> {
+    if pattern.is_empty() {
+        return Err(RouteError::Empty);
+    }
+    if !pattern.starts_with('/') {
+        return Err(RouteError::NoLeadingSlash);
+    }
+
+    let mut names = Vec::new();
+    for segment in pattern.split('/') {
+        if let Some(name) = segment
+            .strip_prefix(':')
+            .or_else(|| segment.strip_prefix('*'))
+        {
+            if names.contains(&name) {
+                return Err(RouteError::DuplicateParam(name.to_string()));
+            }
+            names.push(name);
+        }
+    }
+
+    Ok(())
+}
+
+/// A route which could not be registered, returned as `Error::RouteRegistration` by the `try_`
+/// methods of `Router`, and naming the route it conflicts with when displayed.
+#[derive(Debug)]
+pub struct RouteRegistrationError {
+    /// The method the route was registered for, or `None` for a route matching any method.
+    pub method: Option<Method>,
+    /// The full pattern of the route, including the prefix of any enclosing group.
+    pub pattern: String,
+    /// Why the route could not be registered.
+    pub reason: RouteError,
+}
+
+impl std::fmt::Display for RouteRegistrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.method {
+            Some(method) => write!(
+                f,
+                "failed to register {} route \"{}\"",
+                method.to_string(),
+                self.pattern
+            )?,
+            None => write!(f, "failed to register route \"{}\"", self.pattern)?,
+        }
+
+        match &self.reason {
+            RouteError::Conflict { with } => write!(
+                f,
+                ": it conflicts with the previously registered route \"{}\"",
+                with
+            ),
+            RouteError::Insert(error) => write!(f, ": {}", error),
+            RouteError::Empty => write!(f, ": patterns cannot be empty"),
+            RouteError::NoLeadingSlash => write!(f, ": patterns must start with \"/\""),
+            RouteError::DuplicateParam(name) => {
+                write!(f, ": the parameter \"{}\" is used more than once", name)
+            }
+        }
     }
 }
 
+fn allowed_methods<D>(handlers: &HashMap<Method, Node<Route<D>>>, path: &str) -> Vec<Method> {
+    Method::all()
+        .into_iter()
+        .filter(
+            |method| matches!(handlers.get(method), Some(handlers) if handlers.at(path).is_ok()),
+        )
+        .collect()
+}
+
+fn allow_header(allowed: &[Method], with_options: bool) -> String {
+    let mut methods: Vec<String> = allowed.iter().map(Method::to_string).collect();
+    if with_options && !allowed.contains(&Method::Options) {
+        methods.push(Method::Options.to_string());
+    }
+    methods.join(", ")
+}
+
+/// Build a `Router` for the incoming request with `build`, which is given the `Env` to construct
+/// any shared data from, and run it. Reduces the `#[event(fetch)]` entrypoint to a single call.
+/// ```ignore
+/// #[event(fetch)]
+/// pub async fn main(req: Request, env: Env) -> Result<Response> {
+///     fetch_router(req, env, |_env| Router::new().get("/", |_, _| Response::ok("Hello"))).await
+/// }
+/// ```
+pub async fn fetch_router<'a, D: 'a>(
+    req: Request,
+    env: Env,
+    build: impl FnOnce(&Env) -> Router<'a, D>,
+) -> Result<Response> {
+    build(&env).run(req, env).await
+}
+
 impl From<matchit::Params<'_, '_>> for RouteParams {
     fn from(p: matchit::Params) -> Self {
         let mut route_params = RouteParams(HashMap::new());
@@ -389,3 +1706,156 @@ impl From<matchit::Params<'_, '_>> for RouteParams {
         route_params
     }
 }
+
+#[test]
+fn allow_header_lists_allowed_methods() {
+    let allowed = [Method::Get, Method::Post];
+    assert_eq!(allow_header(&allowed, false), "GET, POST");
+    assert_eq!(allow_header(&allowed, true), "GET, POST, OPTIONS");
+    assert_eq!(
+        allow_header(&[Method::Get, Method::Options], true),
+        "GET, OPTIONS"
+    );
+}
+
+#[test]
+fn route_insert_errors_name_the_conflicting_route() {
+    let mut node = Node::new();
+    node.insert("/users/:id", ()).unwrap();
+    let e = node.insert("/users/:name", ()).unwrap_err();
+
+    let failure = RouteRegistrationError {
+        method: Some(Method::Get),
+        pattern: "/users/:name".into(),
+        reason: e.into(),
+    };
+
+    assert_eq!(
+        failure.to_string(),
+        "failed to register GET route \"/users/:name\": it conflicts with the previously registered route \"/users/:id\""
+    );
+}
+
+#[test]
+fn try_routes_return_the_registration_error() {
+    fn handler(_: Request, _: RouteContext<()>) -> Result<Response> {
+        Response::empty()
+    }
+
+    let result = Router::new()
+        .get("/users/:id", handler)
+        .try_get("/users/:name", handler);
+    match result {
+        Err(Error::RouteRegistration(RouteRegistrationError {
+            method: Some(Method::Get),
+            pattern,
+            reason: RouteError::Conflict { with },
+        })) => {
+            assert_eq!(pattern, "/users/:name");
+            assert_eq!(with, "/users/:id");
+        }
+        _ => panic!("expected a conflicting route"),
+    }
+}
+
+#[test]
+fn encoded_traversal_is_detected() {
+    assert!(is_traversal("/static/..%2F..%2Fsecret"));
+    assert!(is_traversal("/static/%2e%2e/secret"));
+    assert!(is_traversal("/static/..%5Csecret"));
+    assert!(!is_traversal("/static/app..js"));
+    assert!(!is_traversal("/static/css/app.css"));
+}
+
+#[test]
+fn json_errors_follow_the_accept_header() {
+    assert!(prefers_json(Some("application/json")));
+    assert!(prefers_json(Some(
+        "text/plain, application/problem+json;q=0.9"
+    )));
+    assert!(!prefers_json(Some(
+        "text/html,application/xhtml+xml,*/*;q=0.8"
+    )));
+    assert!(!prefers_json(None));
+}
+
+#[test]
+fn client_errors_are_answered_with_their_status() {
+    assert_eq!(
+        client_error(&Error::BodyTooLarge { limit: 1024 }),
+        Some(("Payload Too Large".into(), 413))
+    );
+    assert_eq!(
+        client_error(&Error::InvalidUtf8),
+        Some(("Bad Request".into(), 400))
+    );
+    assert_eq!(
+        client_error(&Error::ContentLengthMismatch {
+            expected: 10,
+            actual: 4
+        }),
+        Some(("Bad Request".into(), 400))
+    );
+    assert_eq!(
+        client_error(&Error::UnsupportedMediaType),
+        Some(("Unsupported Media Type".into(), 415))
+    );
+    assert_eq!(
+        client_error(&Error::Json(("missing field `name`".into(), 400))),
+        Some(("missing field `name`".into(), 400))
+    );
+    assert_eq!(client_error(&Error::Json(("oops".into(), 500))), None);
+    assert_eq!(client_error(&Error::RustError("oops".into())), None);
+}
+
+#[test]
+fn hosts_match_patterns_with_placeholders() {
+    let params = match_host(":tenant.example.com", "Acme.Example.com:8787").unwrap();
+    assert_eq!(params.get("tenant").map(String::as_str), Some("acme"));
+    assert!(match_host("api.example.com", "API.example.com.")
+        .unwrap()
+        .is_empty());
+    assert!(match_host(":tenant.example.com", "example.com").is_none());
+    assert!(match_host(":tenant.example.com", "a.b.example.com").is_none());
+    assert!(match_host("api.example.com", "www.example.com").is_none());
+}
+
+#[test]
+fn invalid_patterns_are_rejected() {
+    assert!(validate_pattern("/users/:id/posts/:post_id").is_ok());
+    assert!(matches!(validate_pattern(""), Err(RouteError::Empty)));
+    assert!(matches!(
+        validate_pattern("users/:id"),
+        Err(RouteError::NoLeadingSlash)
+    ));
+
+    let failure = RouteRegistrationError {
+        method: Some(Method::Get),
+        pattern: "/users/:id/posts/*id".into(),
+        reason: validate_pattern("/users/:id/posts/*id").unwrap_err(),
+    };
+    assert_eq!(
+        failure.to_string(),
+        "failed to register GET route \"/users/:id/posts/*id\": the parameter \"id\" is used more than once"
+    );
+}
+
+#[test]
+fn redirect_targets_are_interpolated() {
+    let params = RouteParams(
+        [("id", "42"), ("path", "guides/setup")]
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+    );
+
+    assert_eq!(
+        interpolate_params("/new/:id?tab=1", &params),
+        "/new/42?tab=1"
+    );
+    assert_eq!(
+        interpolate_params("https://docs.example.com:8443/*path", &params),
+        "https://docs.example.com:8443/guides/setup"
+    );
+    assert_eq!(interpolate_params("/users/:name", &params), "/users/:name");
+}
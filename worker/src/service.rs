@@ -0,0 +1,85 @@
+use crate::{error::Error, request::Request, response::Response, Result};
+
+use js_sys::{Array, Function, Promise};
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use worker_sys::{Fetcher, Response as EdgeResponse};
+
+/// A [service binding](https://developers.cloudflare.com/workers/runtime-apis/bindings/service-bindings/)
+/// to another Worker, which can be sent requests or, when the bound Worker exports a
+/// `WorkerEntrypoint`, have its methods called over RPC.
+/// ```ignore
+/// let auth = env.service("AUTH")?;
+/// let user: User = auth.call("verify", (token,)).await?;
+/// let res = auth.fetch_with_str("https://auth/health").await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Service {
+    inner: Fetcher,
+}
+
+impl Service {
+    /// Send a Request to the bound Worker, as if it were sent to its `fetch` handler.
+    pub async fn fetch_with_request(&self, req: Request) -> Result<Response> {
+        let promise = self.inner.fetch_with_request(req.inner());
+        let response = JsFuture::from(promise).await?;
+        Ok(response.dyn_into::<EdgeResponse>()?.into())
+    }
+
+    /// Construct a Request from a URL and send it to the bound Worker.
+    pub async fn fetch_with_str(&self, url: &str) -> Result<Response> {
+        let promise = self.inner.fetch_with_str(url);
+        let response = JsFuture::from(promise).await?;
+        Ok(response.dyn_into::<EdgeResponse>()?.into())
+    }
+
+    /// Call the RPC method named `method` on the bound entrypoint, decoding its result as `R`.
+    ///
+    /// `args` is encoded as JSON: a tuple or `Vec` is passed as positional arguments, `()` as no
+    /// arguments, and any other value as the single argument.
+    pub async fn call<A: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        args: A,
+    ) -> Result<R> {
+        let args = match js_sys::JSON::parse(&serde_json::to_string(&args)?)? {
+            args if args.is_null() => Array::new(),
+            args if Array::is_array(&args) => args.unchecked_into(),
+            arg => Array::of1(&arg),
+        };
+
+        let result = self.call_raw(method, &args).await?;
+        let json = match js_sys::JSON::stringify(&result)?.as_string() {
+            Some(json) => json,
+            // `undefined` has no JSON representation
+            None => "null".to_string(),
+        };
+        serde_json::from_str(&json).map_err(Error::from)
+    }
+
+    /// Call the RPC method named `method` on the bound entrypoint with JavaScript arguments,
+    /// returning its result once resolved. Use this for arguments or results which cannot be
+    /// represented as JSON, such as streams or stubs.
+    pub async fn call_raw(&self, method: &str, args: &Array) -> Result<JsValue> {
+        // Weird rust-analyzer bug is causing it to think Reflect::get is unsafe
+        #[allow(unused_unsafe)]
+        let func = unsafe { js_sys::Reflect::get(&self.inner, &JsValue::from(method)) }?;
+        let func: Function = func.dyn_into().map_err(|_| {
+            Error::JsError(format!("service does not have an RPC method `{}`", method))
+        })?;
+
+        let result = func.apply(&self.inner, args)?;
+        Ok(JsFuture::from(Promise::resolve(&result)).await?)
+    }
+
+    pub fn inner(&self) -> &Fetcher {
+        &self.inner
+    }
+}
+
+impl From<Fetcher> for Service {
+    fn from(inner: Fetcher) -> Self {
+        Self { inner }
+    }
+}
@@ -0,0 +1,212 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use crate::{
+    csrf::cookie_value, date::Date, middleware::Middleware, request::Request, response::Response,
+    router::RouteContext, Result,
+};
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use worker_kv::KvStore;
+use worker_sys::global::random_uuid;
+
+const SESSION_KEY: &str = "session";
+
+/// A session loaded by `Sessions`, holding the data stored for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session<T> {
+    pub id: String,
+    pub data: T,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredSession<T> {
+    expires_at: u64,
+    data: T,
+}
+
+/// Sessions holding `T` as JSON in a Workers KV namespace, identified by a random ID kept in a
+/// cookie. Used as middleware, `Sessions` loads the session of each request before the handler
+/// runs, which then gets it with `Sessions::current`.
+///
+/// Sessions expire `ttl` after they were last written. Expired sessions are treated as absent,
+/// even before KV has removed them.
+/// ```ignore
+/// let sessions = Sessions::<User>::new("SESSIONS", Duration::from_secs(86400));
+///
+/// // when logging in
+/// let id = sessions.create(&ctx, &user).await?;
+/// Response::ok("welcome")?.append_header("set-cookie", &sessions.set_cookie(&id))
+///
+/// // when handling a later request
+/// Router::with_data(sessions.clone()).middleware(sessions).get_async("/me", |req, ctx| async move {
+///     match ctx.data().current(&req, &ctx).await? {
+///         Some(session) => Response::from_json(&session.data),
+///         None => Response::error("Unauthorized", 401),
+///     }
+/// })
+/// ```
+pub struct Sessions<T> {
+    binding: String,
+    ttl: Duration,
+    cookie_name: String,
+    data: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Sessions<T> {
+    fn clone(&self) -> Self {
+        Self {
+            binding: self.binding.clone(),
+            ttl: self.ttl,
+            cookie_name: self.cookie_name.clone(),
+            data: PhantomData,
+        }
+    }
+}
+
+impl<T> Sessions<T>
+where
+    T: Serialize + DeserializeOwned + Clone + 'static,
+{
+    /// Store sessions in the KV namespace bound as `binding`, expiring `ttl` after they were last
+    /// written. KV cannot expire keys sooner than 60 seconds, so shorter sessions stay in KV for
+    /// that long, though they are treated as absent once expired. The session cookie is named
+    /// `session`.
+    pub fn new(binding: &str, ttl: Duration) -> Self {
+        Self {
+            binding: binding.to_string(),
+            ttl,
+            cookie_name: "session".into(),
+            data: PhantomData,
+        }
+    }
+
+    /// Set the name of the cookie holding the session ID.
+    pub fn cookie_name(mut self, name: &str) -> Self {
+        self.cookie_name = name.to_string();
+        self
+    }
+
+    /// Store a new session holding `data`, returning its ID. Send it to the client with
+    /// `set_cookie`.
+    pub async fn create<D>(&self, ctx: &RouteContext<D>, data: &T) -> Result<String> {
+        let id = format!("{}{}", random_uuid(), random_uuid()).replace('-', "");
+        self.update(ctx, &id, data).await?;
+        Ok(id)
+    }
+
+    /// Get the data of the session with the given ID, or `None` if it does not exist or has
+    /// expired.
+    pub async fn get<D>(&self, ctx: &RouteContext<D>, id: &str) -> Result<Option<T>> {
+        if !is_session_id(id) {
+            return Ok(None);
+        }
+
+        let stored = match self.store(ctx)?.get(&session_key(id)).await? {
+            Some(stored) => stored.as_string(),
+            None => return Ok(None),
+        };
+        let stored: StoredSession<T> = serde_json::from_str(&stored)?;
+        if stored.expires_at <= Date::now().as_millis() {
+            return Ok(None);
+        }
+        Ok(Some(stored.data))
+    }
+
+    /// Replace the data of the session with the given ID, which then expires `ttl` from now.
+    pub async fn update<D>(&self, ctx: &RouteContext<D>, id: &str, data: &T) -> Result<()> {
+        let stored = StoredSession {
+            expires_at: Date::now().as_millis() + self.ttl.as_millis() as u64,
+            data,
+        };
+        self.store(ctx)?
+            .put(&session_key(id), serde_json::to_string(&stored)?)?
+            .expiration_ttl(self.ttl.as_secs().max(60))
+            .execute()
+            .await?;
+        Ok(())
+    }
+
+    /// Delete the session with the given ID, e.g. when logging out. Send `clear_cookie` to the
+    /// client as well.
+    pub async fn destroy<D>(&self, ctx: &RouteContext<D>, id: &str) -> Result<()> {
+        self.store(ctx)?.delete(&session_key(id)).await?;
+        Ok(())
+    }
+
+    /// Get the session of `req`, as loaded by the `Sessions` middleware, or load it now if the
+    /// middleware did not run for this route.
+    pub async fn current<D>(
+        &self,
+        req: &Request,
+        ctx: &RouteContext<D>,
+    ) -> Result<Option<Session<T>>> {
+        ctx.get_or_init_async(SESSION_KEY, || async {
+            let id = match self.session_id(req)? {
+                Some(id) => id,
+                None => return Ok(None),
+            };
+            let data = self.get(ctx, &id).await?;
+            Ok(data.map(|data| Session { id, data }))
+        })
+        .await
+    }
+
+    /// Get the session ID sent in `req`'s session cookie, if any.
+    pub fn session_id(&self, req: &Request) -> Result<Option<String>> {
+        let cookies = req.headers().get("cookie")?.unwrap_or_default();
+        Ok(cookie_value(&cookies, &self.cookie_name).map(str::to_string))
+    }
+
+    /// The value of the `Set-Cookie` header storing the session ID `id` with the client. The
+    /// cookie is `Secure`, `HttpOnly` and `SameSite=Lax`, and expires along with the session.
+    pub fn set_cookie(&self, id: &str) -> String {
+        format!(
+            "{}={}; Path=/; Max-Age={}; Secure; HttpOnly; SameSite=Lax",
+            self.cookie_name,
+            id,
+            self.ttl.as_secs()
+        )
+    }
+
+    /// The value of the `Set-Cookie` header removing the session cookie from the client.
+    pub fn clear_cookie(&self) -> String {
+        format!(
+            "{}=; Path=/; Max-Age=0; Secure; HttpOnly; SameSite=Lax",
+            self.cookie_name
+        )
+    }
+
+    fn store<D>(&self, ctx: &RouteContext<D>) -> Result<KvStore> {
+        ctx.kv(&self.binding)
+    }
+}
+
+#[async_trait(?Send)]
+impl<D, T> Middleware<D> for Sessions<T>
+where
+    T: Serialize + DeserializeOwned + Clone + 'static,
+{
+    async fn handle(&self, req: &Request, ctx: &RouteContext<D>) -> Result<Option<Response>> {
+        self.current(req, ctx).await?;
+        Ok(None)
+    }
+}
+
+fn session_key(id: &str) -> String {
+    format!("session:{}", id)
+}
+
+/// Whether `id` could have been created by `Sessions::create`, so that arbitrary cookie values are
+/// not looked up in KV.
+fn is_session_id(id: &str) -> bool {
+    id.len() == 64 && id.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[test]
+fn only_generated_ids_are_looked_up() {
+    assert!(is_session_id(&"ab12".repeat(16)));
+    assert!(!is_session_id("ab12"));
+    assert!(!is_session_id(&"../x".repeat(16)));
+}
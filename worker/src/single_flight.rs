@@ -0,0 +1,125 @@
+use std::{cell::RefCell, collections::HashMap, future::Future, rc::Rc};
+
+use crate::{Error, Result};
+
+use futures::future::{FutureExt, LocalBoxFuture, Shared};
+
+type InFlight<T> = Shared<LocalBoxFuture<'static, std::result::Result<T, Rc<Error>>>>;
+
+/// Deduplicates concurrent work within an isolate: while the work for a key is in flight, callers
+/// asking for the same key await it instead of starting their own, and all of them receive its
+/// result. This keeps a burst of identical cache misses from all reaching the origin at once.
+///
+/// Since a `Router` is built for each request, keep the `SingleFlight` somewhere which outlives
+/// it, such as a `thread_local!`. Results are cloned for each caller, so share something cheap to
+/// clone, like the bytes of a body rather than a `Response`. When the work fails, every caller
+/// receives the error's message as an `Error::RustError`.
+/// ```ignore
+/// thread_local! {
+///     static ORIGIN: SingleFlight<Vec<u8>> = SingleFlight::new();
+/// }
+///
+/// let path = req.path();
+/// let url = format!("https://origin.example.com{}", path);
+/// let body = ORIGIN
+///     .with(|origin| origin.clone())
+///     .run(&path, || async move { Fetch::Url(url.parse()?).send().await?.bytes().await })
+///     .await?;
+/// ```
+pub struct SingleFlight<T> {
+    in_flight: Rc<RefCell<HashMap<String, InFlight<T>>>>,
+}
+
+impl<T: Clone + 'static> SingleFlight<T> {
+    /// Construct a new `SingleFlight`, with no work in flight.
+    pub fn new() -> Self {
+        Self {
+            in_flight: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    /// Run `work` for `key`, unless work for the same key is already in flight, in which case
+    /// its result is awaited instead and `work` is never called.
+    pub async fn run<F, Fut>(&self, key: &str, work: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>> + 'static,
+    {
+        let existing = self.in_flight.borrow().get(key).cloned();
+        let in_flight = match existing {
+            Some(in_flight) => in_flight,
+            None => {
+                let work = work();
+                let registry = self.in_flight.clone();
+                let owned_key = key.to_string();
+                let in_flight = async move {
+                    let result = work.await.map_err(Rc::new);
+                    registry.borrow_mut().remove(&owned_key);
+                    result
+                }
+                .boxed_local()
+                .shared();
+
+                self.in_flight
+                    .borrow_mut()
+                    .insert(key.to_string(), in_flight.clone());
+                in_flight
+            }
+        };
+
+        in_flight.await.map_err(|e| Error::RustError(e.to_string()))
+    }
+}
+
+impl<T> Clone for SingleFlight<T> {
+    fn clone(&self) -> Self {
+        Self {
+            in_flight: self.in_flight.clone(),
+        }
+    }
+}
+
+impl<T: Clone + 'static> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn concurrent_work_for_a_key_runs_once() {
+    use std::cell::Cell;
+
+    let calls = Rc::new(Cell::new(0));
+    let flight = SingleFlight::new();
+    let work = |value: u32| {
+        let calls = calls.clone();
+        move || async move {
+            calls.set(calls.get() + 1);
+            // stay in flight until the other callers have been polled
+            let mut yielded = false;
+            futures::future::poll_fn(|cx| {
+                if yielded {
+                    std::task::Poll::Ready(())
+                } else {
+                    yielded = true;
+                    cx.waker().wake_by_ref();
+                    std::task::Poll::Pending
+                }
+            })
+            .await;
+            Ok(value)
+        }
+    };
+
+    let (a, b, c) = futures::executor::block_on(async {
+        futures::join!(
+            flight.run("a", work(1)),
+            flight.run("a", work(2)),
+            flight.run("b", work(3))
+        )
+    });
+
+    assert_eq!((a.unwrap(), b.unwrap(), c.unwrap()), (1, 1, 3));
+    assert_eq!(calls.get(), 2);
+    assert!(flight.in_flight.borrow().is_empty());
+}
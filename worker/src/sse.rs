@@ -0,0 +1,122 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::Duration;
+
+use futures::{future, stream, Stream, StreamExt, TryStream, TryStreamExt};
+
+use crate::{error::Error, time::delay, Result};
+
+/// An event sent to the client of a [server-sent events](https://developer.mozilla.org/en-US/docs/Web/API/Server-sent_events)
+/// stream, created by `Response::from_sse`.
+/// ```ignore
+/// let event = SseEvent::new("{\"price\":42}").event("tick").id("17");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SseEvent {
+    event: Option<String>,
+    data: String,
+    id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl SseEvent {
+    /// Construct an event carrying `data`, which may span several lines.
+    pub fn new(data: impl Into<String>) -> Self {
+        Self {
+            data: data.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the type of the event, dispatched by the client's `EventSource` to listeners for it.
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// Set the ID of the event, sent back by the client in `Last-Event-ID` when reconnecting.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Set how long the client waits before reconnecting once the stream closes, truncated to
+    /// whole milliseconds.
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+}
+
+impl Display for SseEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        // fields cannot contain line breaks, which would end them early
+        let single_line = |value: &str| value.replace(['\r', '\n'], " ");
+
+        if let Some(event) = &self.event {
+            writeln!(f, "event: {}", single_line(event))?;
+        }
+        if let Some(id) = &self.id {
+            writeln!(f, "id: {}", single_line(id))?;
+        }
+        if let Some(retry) = self.retry {
+            writeln!(f, "retry: {}", retry.as_millis())?;
+        }
+        for line in self.data.lines() {
+            writeln!(f, "data: {}", line)?;
+        }
+        if self.data.is_empty() {
+            writeln!(f, "data:")?;
+        }
+        writeln!(f)
+    }
+}
+
+/// The comment sent to keep an idle stream open, which clients ignore.
+const KEEPALIVE: &[u8] = b": ping\n\n";
+
+/// Encode `events` as the body of a server-sent events stream, interleaved with a keepalive
+/// comment every `keepalive_interval`. The pending timeout is cleared when the stream is dropped,
+/// whether it ends or is cancelled because the client disconnected.
+pub(crate) fn into_sse_stream<S>(
+    events: S,
+    keepalive_interval: Option<Duration>,
+) -> impl Stream<Item = Result<Vec<u8>>>
+where
+    S: TryStream<Ok = SseEvent> + 'static,
+    S::Error: Into<Error>,
+{
+    let events = events
+        .map_ok(|event| event.to_string().into_bytes())
+        .map_err(Into::into)
+        .into_stream();
+
+    match keepalive_interval {
+        None => events.boxed_local(),
+        Some(interval) => {
+            // `None` marks the end of the events, after which the keepalive stream is dropped
+            let events = events.map(Some).chain(stream::once(future::ready(None)));
+            let keepalive = stream::unfold((), move |_| async move {
+                delay(interval).await;
+                Some((Some(Ok(KEEPALIVE.to_vec())), ()))
+            });
+
+            stream::select(events, keepalive)
+                .take_while(|item| future::ready(item.is_some()))
+                .filter_map(future::ready)
+                .boxed_local()
+        }
+    }
+}
+
+#[test]
+fn sse_events_are_encoded_line_by_line() {
+    let event = SseEvent::new("first\nsecond")
+        .event("update\nevil")
+        .id("7")
+        .retry(Duration::from_secs(3));
+    assert_eq!(
+        event.to_string(),
+        "event: update evil\nid: 7\nretry: 3000\ndata: first\ndata: second\n\n"
+    );
+    assert_eq!(SseEvent::new("").to_string(), "data:\n\n");
+}
@@ -0,0 +1,115 @@
+//! Timing utilities which work in Workers, where `std::time::Instant` and `std::thread::sleep`
+//! are unavailable.
+//!
+//! **Note:** to mitigate timing attacks, the Workers runtime only advances its clock when the
+//! Worker performs I/O, such as a fetch or a KV read, so timing pure computation always measures
+//! zero. Durations measured across I/O are accurate.
+//! ```ignore
+//! use worker::time::{delay, Stopwatch};
+//!
+//! let stopwatch = Stopwatch::new();
+//! let res = Fetch::Url(url).send().await?;
+//! console_log!("upstream took {:?}", stopwatch.elapsed());
+//!
+//! delay(Duration::from_millis(250)).await;
+//! ```
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use js_sys::Promise;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use worker_sys::{global::performance_now, WorkerGlobalScope};
+
+/// The time elapsed since the isolate's clock origin, from `performance.now()`. Only the
+/// difference between two calls is meaningful.
+pub fn now() -> Duration {
+    from_millis(performance_now())
+}
+
+/// Measures the time elapsed since it was started, e.g. to report how long a handler took.
+#[derive(Debug, Clone, Copy)]
+pub struct Stopwatch {
+    start: f64,
+}
+
+impl Stopwatch {
+    /// Construct a `Stopwatch` started now.
+    pub fn new() -> Self {
+        Self {
+            start: performance_now(),
+        }
+    }
+
+    /// The time elapsed since this `Stopwatch` was started.
+    pub fn elapsed(&self) -> Duration {
+        from_millis(performance_now() - self.start)
+    }
+}
+
+impl Default for Stopwatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get a `Future` which resolves once `duration` has elapsed, using `setTimeout`. Durations
+/// longer than `i32::MAX` milliseconds are shortened to it. Dropping the `Delay` before then
+/// cancels the timeout with `clearTimeout`.
+pub fn delay(duration: Duration) -> Delay {
+    let timeout = duration.as_millis().min(i32::MAX as u128) as i32;
+    let mut handle = None;
+    let promise = Promise::new(&mut |resolve, reject| match global()
+        .set_timeout_with_callback_and_timeout(&resolve, timeout)
+    {
+        Ok(id) => handle = Some(id),
+        Err(e) => {
+            let _ = reject.call1(&JsValue::UNDEFINED, &e);
+        }
+    });
+
+    Delay {
+        inner: JsFuture::from(promise),
+        handle,
+    }
+}
+
+/// A `Future` which resolves once a duration has elapsed. Created by calling `delay`.
+pub struct Delay {
+    inner: JsFuture,
+    /// The handle of the pending timeout, taken once it has fired.
+    handle: Option<JsValue>,
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let poll = Pin::new(&mut self.inner).poll(cx);
+        if poll.is_ready() {
+            self.handle = None;
+        }
+        poll.map(|_| ())
+    }
+}
+
+impl Drop for Delay {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            global().clear_timeout_with_handle(&handle);
+        }
+    }
+}
+
+fn global() -> WorkerGlobalScope {
+    js_sys::global().unchecked_into()
+}
+
+fn from_millis(millis: f64) -> Duration {
+    Duration::from_secs_f64(millis.max(0.0) / 1000.0)
+}
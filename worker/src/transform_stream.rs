@@ -0,0 +1,109 @@
+use std::result::Result as StdResult;
+
+use crate::{mime::ContentEncoding, request_init::set_prop, Error, Result};
+
+use js_sys::{Object, Uint8Array};
+use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+use worker_sys::{
+    transform_stream::TransformStreamDefaultController, CompressionStream,
+    TransformStream as EdgeTransformStream,
+};
+
+/// A [TransformStream](https://developer.mozilla.org/en-US/docs/Web/API/TransformStream)
+/// representation, which rewrites a streamed body chunk by chunk as it passes through, without
+/// buffering the whole body. Pass it to `Response::pipe_through` to transform a response.
+/// ```ignore
+/// let upper = TransformStream::new(|chunk| Ok(chunk.to_ascii_uppercase()))?;
+/// Fetch::Url(url).send().await?.pipe_through(upper)
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransformStream {
+    inner: EdgeTransformStream,
+}
+
+impl TransformStream {
+    /// Construct a `TransformStream` which passes each chunk through `transform`, emitting the
+    /// bytes it returns. Returning an empty chunk emits nothing, and returning an error aborts the
+    /// stream.
+    pub fn new(transform: impl FnMut(Vec<u8>) -> Result<Vec<u8>> + 'static) -> Result<Self> {
+        Self::with_flush(transform, || Ok(Vec::new()))
+    }
+
+    /// Construct a `TransformStream` like `TransformStream::new`, which also emits the bytes
+    /// returned by `flush` once every chunk has been transformed. This allows a transform to hold
+    /// back the end of one chunk, e.g. a partial match, until it has seen the next.
+    pub fn with_flush(
+        mut transform: impl FnMut(Vec<u8>) -> Result<Vec<u8>> + 'static,
+        flush: impl FnOnce() -> Result<Vec<u8>> + 'static,
+    ) -> Result<Self> {
+        let transform = Closure::wrap(Box::new(
+            move |chunk: JsValue, controller: TransformStreamDefaultController| {
+                let output = transform(Uint8Array::new(&chunk).to_vec())?;
+                enqueue(&controller, &output)
+            },
+        )
+            as Box<dyn FnMut(JsValue, TransformStreamDefaultController) -> StdResult<(), JsValue>>);
+        let flush = Closure::once(move |controller: TransformStreamDefaultController| {
+            let output = flush()?;
+            enqueue(&controller, &output)
+        });
+
+        let transformer = Object::new();
+        set_prop(
+            &transformer,
+            &JsValue::from("transform"),
+            &transform.into_js_value(),
+        );
+        set_prop(
+            &transformer,
+            &JsValue::from("flush"),
+            &flush.into_js_value(),
+        );
+
+        Ok(Self {
+            inner: EdgeTransformStream::new_with_transformer(&transformer)?,
+        })
+    }
+
+    /// Construct a `TransformStream` which passes every chunk through unchanged.
+    pub fn identity() -> Result<Self> {
+        Ok(Self {
+            inner: EdgeTransformStream::new()?,
+        })
+    }
+
+    /// Construct a `TransformStream` compressing every chunk with `encoding`, using the runtime's
+    /// `CompressionStream`. Returns an error for `ContentEncoding::Brotli`, which it does not
+    /// support.
+    pub fn compress(encoding: ContentEncoding) -> Result<Self> {
+        if encoding == ContentEncoding::Brotli {
+            return Err(Error::RustError(
+                "CompressionStream does not support Brotli".into(),
+            ));
+        }
+
+        // a CompressionStream is a readable and writable pair, like a TransformStream
+        let stream = CompressionStream::new(encoding.as_str())?;
+        Ok(Self {
+            inner: stream.unchecked_into(),
+        })
+    }
+
+    pub fn inner(&self) -> &EdgeTransformStream {
+        &self.inner
+    }
+}
+
+impl From<EdgeTransformStream> for TransformStream {
+    fn from(inner: EdgeTransformStream) -> Self {
+        Self { inner }
+    }
+}
+
+fn enqueue(controller: &TransformStreamDefaultController, output: &[u8]) -> StdResult<(), JsValue> {
+    if output.is_empty() {
+        return Ok(());
+    }
+
+    controller.enqueue(&Uint8Array::from(output).into())
+}